@@ -6,7 +6,6 @@
 from xml.dom.minidom import parseString
 import os
 import json
-from PIL import Image
 
 f = open("chartsymbols.xml", "r")
 lines = f.read()
@@ -90,6 +89,7 @@ def read_sprites(render_img: bool = False, only_names: set = None):
             x = locEle[0].attributes["x"].value
             y = locEle[0].attributes["y"].value
             if render_img:
+                from PIL import Image
                 im = Image.open(os.path.join(script_dir, "rastersymbols-day.png"))
                 # (left, upper, right, lower) = (20, 20, 100, 100)
                 im = im.crop((int(x), int(y), int(x) + int(width), int(y) + int(height)))
@@ -104,6 +104,40 @@ def read_sprites(render_img: bool = False, only_names: set = None):
     print(json.dumps(result, indent=2))
 
 
+def read_lookups():
+    """
+    The look-up tables used by the s52 module (src/lookups.json) in their original order, the order
+    is significant as the first best match wins.
+    python3 -c 'import chartsymbolextractor as c; c.write_lookups()' writes ../../src/lookups.json
+    """
+    dom = parseString(lines)
+    result = list()
+    for lookup in dom.getElementsByTagName("lookup"):
+        def text(tag):
+            ele = lookup.getElementsByTagName(tag)
+            if len(ele) > 0 and ele[0].firstChild is not None:
+                return ele[0].firstChild.nodeValue
+            return ""
+        attributes = sorted(lookup.getElementsByTagName("attrib-code"), key=lambda a: int(a.attributes["index"].value))
+        result.append({
+            "name": lookup.attributes["name"].value,
+            "type": text("type"),
+            "table": text("table-name"),
+            "disp_prio": text("disp-prio"),
+            "display_cat": text("display-cat"),
+            "attributes": [a.firstChild.nodeValue for a in attributes if a.firstChild is not None],
+            "instruction": text("instruction")
+        })
+    return result
+
+
+def write_lookups():
+    with open(os.path.join(script_dir, "../../src/lookups.json"), "w") as out:
+        out.write("[\n")
+        out.write(",\n".join(json.dumps(ea) for ea in read_lookups()))
+        out.write("\n]\n")
+
+
 def read_colors():
     dom = parseString(lines)
     result = dict()
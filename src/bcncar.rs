@@ -0,0 +1,39 @@
+use crate::geojson_builder::JsonObject;
+use geojson::Geometry;
+use crate::bcnspp::process_beacon;
+use crate::boycar::Catcam;
+use crate::colors::Colpat;
+
+/// BCNCAR, Beacon Cardinal
+/// Geometric primitives: Point
+///
+/// Set Attribute_A: 	(!)BCNSHP; (!)CATCAM; (!)COLOUR; (!?)COLPAT; CONDTN; CONVIS; CONRAD; DATEND; DATSTA; ELEVAT; HEIGHT; MARSYS; NATCON; NOBJNM; OBJNAM; PEREND; PERSTA; STATUS; VERACC; VERDAT; VERLEN;
+/// Set Attribute_B: 	INFORM; NINFOM; NTXTDS; PICREP; SCAMAX; SCAMIN; TXTDSC;
+/// Set Attribute_C: 	RECDAT; RECIND; SORDAT; SORIND;
+///
+/// Definition:
+///     A cardinal beacon is used to indicate the direction from it in which the best navigable water lies, or the side on which to pass a danger. (UKHO NP 735, 5th Edition)
+/// References
+///     INT 1:	IQ 130.3;
+///     S-4:	462.2;
+/// Remarks:
+///     Topmark, light, fog signal, radar reflector and retro-reflector are separate objects.
+/// Distinction:
+///     beacon isolated danger; beacon lateral; beacon safe water; beacon special purpose/general; daymark;
+pub fn process_bcncar(geojson_geom: Geometry, properties: &mut JsonObject) -> Geometry {
+    let colours = Catcam::from_value(properties).map(|c| c.colours()).unwrap_or_default();
+    process_beacon("BCNCAR", geojson_geom, properties, colours, vec![Colpat::HorizontalStripes])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+    use crate::test_util::point_symbol;
+
+    #[test]
+    fn test_process_bcncar() {
+        assert_eq!(Some(json!("BCNGEN70")), point_symbol(process_bcncar, json!({"CATCAM": 2})));
+        assert_eq!(Some(json!("BCNGEN70")), point_symbol(process_bcncar, json!({"BCNSHP": 1, "COLOUR": ["2", "6", "2"]})));
+    }
+}
@@ -0,0 +1,39 @@
+use crate::geojson_builder::JsonObject;
+use geojson::Geometry;
+use crate::bcnspp::process_beacon;
+use crate::colors::{Colpat, Colour};
+
+/// BCNISD, Beacon Isolated Danger
+/// Geometric primitives: Point
+///
+/// Set Attribute_A: 	(!)BCNSHP; (!)COLOUR; (!?)COLPAT; CONDTN; CONVIS; CONRAD; DATEND; DATSTA; ELEVAT; HEIGHT; MARSYS; NATCON; NOBJNM; OBJNAM; PEREND; PERSTA; STATUS; VERACC; VERDAT; VERLEN;
+/// Set Attribute_B: 	INFORM; NINFOM; NTXTDS; PICREP; SCAMAX; SCAMIN; TXTDSC;
+/// Set Attribute_C: 	RECDAT; RECIND; SORDAT; SORIND;
+///
+/// Definition:
+///     An isolated danger beacon is a beacon erected on an isolated danger of limited extent, which has navigable water all around it. (UKHO NP 735, 5th Edition)
+/// References
+///     INT 1:	IQ 130.4;
+///     S-4:	462.3;
+/// Remarks:
+///     Topmark, light, fog signal, radar reflector and retro-reflector are separate objects.
+/// Distinction:
+///     beacon cardinal; beacon lateral; beacon safe water; beacon special purpose/general; daymark;
+pub fn process_bcnisd(geojson_geom: Geometry, properties: &mut JsonObject) -> Geometry {
+    // black with one or more broad horizontal red bands
+    let colours = vec![Colour::Black, Colour::Red, Colour::Black];
+    process_beacon("BCNISD", geojson_geom, properties, colours, vec![Colpat::HorizontalStripes])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+    use crate::test_util::point_symbol;
+
+    #[test]
+    fn test_process_bcnisd() {
+        assert_eq!(Some(json!("BCNGEN76")), point_symbol(process_bcnisd, json!({})));
+        assert_eq!(Some(json!("BCNGEN76")), point_symbol(process_bcnisd, json!({"BCNSHP": 1})));
+    }
+}
@@ -0,0 +1,46 @@
+use crate::geojson_builder::JsonObject;
+use geojson::Geometry;
+use crate::bcnspp::process_beacon;
+use crate::boylat::{Catlam, Marsys};
+use crate::colors::Colpat;
+
+/// BCNLAT, Beacon Lateral
+/// Geometric primitives: Point
+///
+/// Set Attribute_A: 	(!)BCNSHP; (!)CATLAM; (!)COLOUR; (!?)COLPAT; CONDTN; CONVIS; CONRAD; DATEND; DATSTA; ELEVAT; HEIGHT; (?)MARSYS; NATCON; NOBJNM; OBJNAM; PEREND; PERSTA; STATUS; VERACC; VERDAT; VERLEN;
+/// Set Attribute_B: 	INFORM; NINFOM; NTXTDS; PICREP; SCAMAX; SCAMIN; TXTDSC;
+/// Set Attribute_C: 	RECDAT; RECIND; SORDAT; SORIND;
+///
+/// Definition:
+///     A lateral beacon is used to indicate the port or starboard hand side of the route to be followed. They are generally used for well defined channels and are used in conjunction with a conventional direction of buoyage. (UKHO NP 735, 5th Edition)
+/// References
+///     INT 1:	IQ 130.1;
+///     S-4:	461.2-3;
+/// Remarks:
+///     Topmark, light, fog signal, radar reflector and retro-reflector are separate objects.
+/// Distinction:
+///     beacon cardinal; beacon isolated danger; beacon safe water; beacon special purpose/general; daymark;
+pub fn process_bcnlat(geojson_geom: Geometry, properties: &mut JsonObject) -> Geometry {
+    let marsys = Marsys::from_value(properties);
+    let colours = Catlam::from_value(properties).map(|c| c.colours(&marsys)).unwrap_or_default();
+    let pattern = if colours.len() > 1 { vec![Colpat::HorizontalStripes] } else { vec![] };
+    process_beacon("BCNLAT", geojson_geom, properties, colours, pattern)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+    use crate::test_util::point_symbol;
+
+    #[test]
+    fn test_process_bcnlat() {
+        // daybeacons and lighted beacons of US5WA22M
+        assert_eq!(Some(json!("BCNTOW61")), point_symbol(process_bcnlat, json!({"BCNSHP": 4, "CATLAM": 1, "COLOUR": ["4"]})));
+        assert_eq!(Some(json!("BCNSTK61")), point_symbol(process_bcnlat, json!({"BCNSHP": 1, "CATLAM": 1, "COLOUR": ["4"]})));
+        assert_eq!(Some(json!("BCNSTK60")), point_symbol(process_bcnlat, json!({"BCNSHP": 1, "CATLAM": 2, "COLOUR": ["3"]})));
+        // colours of the buoyage region when they are not encoded
+        assert_eq!(Some(json!("BCNSTK60")), point_symbol(process_bcnlat, json!({"BCNSHP": 1, "CATLAM": 1})));
+        assert_eq!(Some(json!("PRICKE03")), point_symbol(process_bcnlat, json!({"BCNSHP": 2, "CATLAM": 1})));
+    }
+}
@@ -0,0 +1,38 @@
+use crate::geojson_builder::JsonObject;
+use geojson::Geometry;
+use crate::bcnspp::process_beacon;
+use crate::colors::{Colpat, Colour};
+
+/// BCNSAW, Beacon Safe Water
+/// Geometric primitives: Point
+///
+/// Set Attribute_A: 	(!)BCNSHP; (!)COLOUR; (!?)COLPAT; CONDTN; CONVIS; CONRAD; DATEND; DATSTA; ELEVAT; HEIGHT; MARSYS; NATCON; NOBJNM; OBJNAM; PEREND; PERSTA; STATUS; VERACC; VERDAT; VERLEN;
+/// Set Attribute_B: 	INFORM; NINFOM; NTXTDS; PICREP; SCAMAX; SCAMIN; TXTDSC;
+/// Set Attribute_C: 	RECDAT; RECIND; SORDAT; SORIND;
+///
+/// Definition:
+///     A safe water beacon is used to indicate that there is navigable water around the mark. (UKHO NP 735, 5th Edition)
+/// References
+///     INT 1:	IQ 130.5;
+///     S-4:	463.1;
+/// Remarks:
+///     Topmark, light, fog signal, radar reflector and retro-reflector are separate objects.
+/// Distinction:
+///     beacon cardinal; beacon isolated danger; beacon lateral; beacon special purpose/general; daymark;
+pub fn process_bcnsaw(geojson_geom: Geometry, properties: &mut JsonObject) -> Geometry {
+    // red and white vertical stripes
+    let colours = vec![Colour::Red, Colour::White];
+    process_beacon("BCNSAW", geojson_geom, properties, colours, vec![Colpat::VerticalStripes])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+    use crate::test_util::point_symbol;
+
+    #[test]
+    fn test_process_bcnsaw() {
+        assert_eq!(Some(json!("BCNSTK78")), point_symbol(process_bcnsaw, json!({"BCNSHP": 1})));
+    }
+}
@@ -0,0 +1,121 @@
+use serde_json::{Value, json};
+use crate::geojson_builder::JsonObject;
+use geojson::Geometry;
+use crate::colors::{Colpat, Colour};
+use crate::{lookups, sprites};
+
+/// BCNSHP
+///ID	Meaning	INT 1	S-4
+/// 1	stake, pole, perch, post	IQ 90, 91;	455.5
+/// 2	withy	IQ 92, 93;	455.6
+/// 3	beacon tower	IQ 110;	457.1
+/// 4	lattice beacon	IQ 111;	457.2
+/// 5	pile beacon	IQ 112;	455.3
+/// 6	cairn	IQ 100;	455.8
+/// 7	buoyant beacon	IQ 113;	457.3
+pub enum Bcnshp {
+    Stake,
+    Withy,
+    Tower,
+    Lattice,
+    Pile,
+    Cairn,
+    Buoyant,
+}
+
+impl Bcnshp {
+    pub fn from_value(properties: &JsonObject) -> Option<Bcnshp> {
+        properties.get("BCNSHP").and_then(|value| {
+            value.as_i64()
+        }).and_then(|n| {
+            match n {
+                1_i64 => Some(Bcnshp::Stake),
+                2_i64 => Some(Bcnshp::Withy),
+                3_i64 => Some(Bcnshp::Tower),
+                4_i64 => Some(Bcnshp::Lattice),
+                5_i64 => Some(Bcnshp::Pile),
+                6_i64 => Some(Bcnshp::Cairn),
+                7_i64 => Some(Bcnshp::Buoyant),
+                _ => None,
+            }
+        })
+    }
+
+    /// The generic symbol of the shape, used when the sprite sheet has no image for the
+    /// look-up table's symbol eg BCNCON81
+    fn symbol(&self) -> &'static str {
+        match self {
+            Bcnshp::Stake | Bcnshp::Withy => "BCNSTK02",
+            Bcnshp::Tower => "BCNTOW01",
+            Bcnshp::Lattice => "BCNLTC01",
+            Bcnshp::Pile | Bcnshp::Cairn | Bcnshp::Buoyant => "BCNGEN01",
+        }
+    }
+}
+
+/// Assigns the symbol `SY` of a beacon from the S-52 look-up table of the class. The colours and
+/// colour pattern the beacon's category prescribes are used when they are not encoded.
+pub fn process_beacon(
+    class: &str,
+    geojson_geom: Geometry,
+    properties: &mut JsonObject,
+    colours: Vec<Colour>,
+    pattern: Vec<Colpat>,
+) -> Geometry {
+    let mut attributes = properties.clone();
+    if Colour::from_value(properties).is_empty() && !colours.is_empty() {
+        attributes.insert(String::from("COLOUR"), json!(colours.iter().map(Colour::value).collect::<Vec<_>>()));
+    }
+    if Colpat::from_value(properties).is_empty() && !pattern.is_empty() {
+        attributes.insert(String::from("COLPAT"), json!(pattern.iter().map(Colpat::value).collect::<Vec<_>>()));
+    }
+    let symbol = lookups::symbol(class, &geojson_geom.value, &attributes)
+        .filter(|symbol| sprites::has_symbol(symbol))
+        .unwrap_or_else(|| {
+            Bcnshp::from_value(properties).map(|shape| shape.symbol()).unwrap_or("BCNDEF13").to_string()
+        });
+    properties.insert(String::from("SY"), Value::String(symbol));
+    geojson_geom
+}
+
+/// BCNSPP, Beacon Special Purpose / General
+/// Geometric primitives: Point
+///
+/// Set Attribute_A: 	(!)BCNSHP; (!)CATSPM; (!)COLOUR; (!?)COLPAT; CONDTN; CONVIS; CONRAD; DATEND; DATSTA; ELEVAT; HEIGHT; (?)MARSYS; NATCON; NOBJNM; OBJNAM; PEREND; PERSTA; STATUS; VERACC; VERDAT; VERLEN;
+/// Set Attribute_B: 	INFORM; NINFOM; NTXTDS; PICREP; SCAMAX; SCAMIN; TXTDSC;
+/// Set Attribute_C: 	RECDAT; RECIND; SORDAT; SORIND;
+///
+/// Definition:
+///     A special purpose beacon is primarily used to indicate an area or feature, the nature of which is apparent from reference to a chart, Sailing Directions or Notices to Mariners. (UKHO NP 735, 5th Edition)
+///     Beacon in general: A beacon whose appearance or purpose is not adequately known.
+/// References
+///     INT 1:	IQ 130.6;
+///     S-4:	456.1;
+/// Remarks:
+///     Topmark, light, fog signal, radar reflector and retro-reflector are separate objects.
+/// Distinction:
+///     beacon cardinal; beacon isolated danger; beacon lateral; beacon safe water; daymark;
+pub fn process_bcnspp(geojson_geom: Geometry, properties: &mut JsonObject) -> Geometry {
+    process_beacon("BCNSPP", geojson_geom, properties, vec![], vec![])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::point_symbol;
+
+    #[test]
+    fn test_process_bcnspp() {
+        // private aids of US5WA22M, stakes with CATSPM 17 and 52
+        assert_eq!(Some(json!("BCNSTK02")), point_symbol(process_bcnspp, json!({"BCNSHP": 1, "CATSPM": ["52"]})));
+        assert_eq!(Some(json!("BCNTOW62")), point_symbol(process_bcnspp, json!({"BCNSHP": 3, "COLOUR": ["6"]})));
+        assert_eq!(Some(json!("BCNTOW91")), point_symbol(process_bcnspp, json!({"BCNSHP": 4, "COLOUR": ["1"]})));
+        assert_eq!(Some(json!("CAIRNS01")), point_symbol(process_bcnspp, json!({"BCNSHP": 6})));
+        // no sprite for BCNCON81, the generic symbol of the shape is used instead
+        let bcncon = json!({"BOYSHP": 1, "COLPAT": ["1", "2"], "COLOUR": ["5", "3", "1", "5"]});
+        assert_eq!(Some(json!("BCNDEF13")), point_symbol(process_bcnspp, bcncon.clone()));
+        let mut stake = bcncon.as_object().unwrap().clone();
+        stake.insert(String::from("BCNSHP"), json!(1));
+        assert_eq!(Some(json!("BCNSTK02")), point_symbol(process_bcnspp, Value::Object(stake)));
+    }
+}
@@ -0,0 +1,82 @@
+use crate::geojson_builder::JsonObject;
+use geojson::Geometry;
+use crate::boyspp::{Boyshp, process_buoy};
+use crate::colors::{Colpat, Colour};
+
+/// CATCAM
+///
+/// Expected input:
+///     ID	Meaning	INT 1	S-4
+///     1	north cardinal mark	IQ 130.3;	462.2;
+///     2	east cardinal mark	IQ 130.3;	462.2;
+///     3	south cardinal mark	IQ 130.3;	462.2;
+///     4	west cardinal mark	IQ 130.3;	462.2;
+pub enum Catcam {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Catcam {
+    pub fn from_value(properties: &JsonObject) -> Option<Catcam> {
+        properties.get("CATCAM").and_then(|value| {
+            value.as_i64()
+        }).and_then(|n| {
+            match n {
+                1_i64 => Some(Catcam::North),
+                2_i64 => Some(Catcam::East),
+                3_i64 => Some(Catcam::South),
+                4_i64 => Some(Catcam::West),
+                _ => None,
+            }
+        })
+    }
+
+    /// Black and yellow bands, black is where the topmark cones point
+    pub fn colours(&self) -> Vec<Colour> {
+        match self {
+            Catcam::North => vec![Colour::Black, Colour::Yellow],
+            Catcam::East => vec![Colour::Black, Colour::Yellow, Colour::Black],
+            Catcam::South => vec![Colour::Yellow, Colour::Black],
+            Catcam::West => vec![Colour::Yellow, Colour::Black, Colour::Yellow],
+        }
+    }
+}
+
+/// BOYCAR, Buoy Cardinal
+/// Geometric primitives: Point
+///
+/// Set Attribute_A: 	(!)BOYSHP; (!)CATCAM; (!)COLOUR; (!?)COLPAT; CONRAD; DATEND; DATSTA; MARSYS; NATCON; NOBJNM; OBJNAM; PEREND; PERSTA; STATUS; VERACC; VERLEN;
+/// Set Attribute_B: 	INFORM; NINFOM; NTXTDS; PICREP; SCAMAX; SCAMIN; TXTDSC;
+/// Set Attribute_C: 	RECDAT; RECIND; SORDAT; SORIND;
+///
+/// Definition:
+///     A cardinal buoy is used to indicate the direction from it in which the best navigable water lies, or the side on which to pass a danger. (UKHO NP 735, 5th Edition)
+/// References
+///     INT 1:	IQ 130.3;
+///     S-4:	462.2;
+/// Remarks:
+///     Topmark, light, fog signal, radar reflector and retro-reflector are separate objects.
+/// Distinction:
+///     buoy installation; buoy isolated danger; buoy lateral; buoy safe water; buoy special purpose/general;
+pub fn process_boycar(geojson_geom: Geometry, properties: &mut JsonObject) -> Geometry {
+    let colours = Catcam::from_value(properties).map(|c| c.colours()).unwrap_or_default();
+    process_buoy("BOYCAR", geojson_geom, properties, Some(Boyshp::Pillar), colours, vec![Colpat::HorizontalStripes])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+    use crate::test_util::point_symbol;
+
+    #[test]
+    fn test_process_boycar() {
+        assert_eq!(Some(json!("BOYPIL68")), point_symbol(process_boycar, json!({"CATCAM": 1})));
+        assert_eq!(Some(json!("BOYPIL70")), point_symbol(process_boycar, json!({"CATCAM": 2})));
+        assert_eq!(Some(json!("BOYPIL69")), point_symbol(process_boycar, json!({"CATCAM": 3})));
+        assert_eq!(Some(json!("BOYSPR71")), point_symbol(process_boycar, json!({"CATCAM": 4, "BOYSHP": 5})));
+        assert_eq!(Some(json!("BOYCAN68")), point_symbol(process_boycar, json!({"CATCAM": 3, "BOYSHP": 2, "COLOUR": ["2", "6"]})));
+    }
+}
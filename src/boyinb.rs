@@ -0,0 +1,37 @@
+use crate::geojson_builder::JsonObject;
+use geojson::Geometry;
+use crate::boyspp::process_buoy;
+
+/// BOYINB, Buoy Installation
+/// Geometric primitives: Point
+///
+/// Set Attribute_A: 	(!)BOYSHP; CATINB; (!)COLOUR; (!?)COLPAT; CONRAD; DATEND; DATSTA; NATCON; NOBJNM; OBJNAM; PEREND; PERSTA; PRODCT; STATUS; VERACC; VERLEN;
+/// Set Attribute_B: 	INFORM; NINFOM; NTXTDS; PICREP; SCAMAX; SCAMIN; TXTDSC;
+/// Set Attribute_C: 	RECDAT; RECIND; SORDAT; SORIND;
+///
+/// Definition:
+///     A buoy used for loading or discharging tankers or other vessels, such as a catenary anchor leg mooring (CALM) or a single buoy mooring (SBM). (adapted from IHO Dictionary, S-32, 5th Edition, 4710)
+/// References
+///     INT 1:	IL 16;
+///     S-4:	445.4;
+/// Remarks:
+///     Installation buoys have no prescribed shape or colours, the generic installation buoy
+///     symbol is used unless a barrel or super-buoy is coloured yellow.
+/// Distinction:
+///     buoy cardinal; buoy isolated danger; buoy lateral; buoy safe water; buoy special purpose/general; mooring/warping facility;
+pub fn process_boyinb(geojson_geom: Geometry, properties: &mut JsonObject) -> Geometry {
+    process_buoy("BOYINB", geojson_geom, properties, None, vec![], vec![])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+    use crate::test_util::point_symbol;
+
+    #[test]
+    fn test_process_boyinb() {
+        assert_eq!(Some(json!("BOYINB01")), point_symbol(process_boyinb, json!({"CATINB": 1})));
+        assert_eq!(Some(json!("BOYSUP62")), point_symbol(process_boyinb, json!({"BOYSHP": 7, "COLOUR": ["6"]})));
+    }
+}
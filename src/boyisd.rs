@@ -0,0 +1,40 @@
+use crate::geojson_builder::JsonObject;
+use geojson::Geometry;
+use crate::boyspp::{Boyshp, process_buoy};
+use crate::colors::{Colpat, Colour};
+
+/// BOYISD, Buoy Isolated Danger
+/// Geometric primitives: Point
+///
+/// Set Attribute_A: 	(!)BOYSHP; (!)COLOUR; (!?)COLPAT; CONRAD; DATEND; DATSTA; MARSYS; NATCON; NOBJNM; OBJNAM; PEREND; PERSTA; STATUS; VERACC; VERLEN;
+/// Set Attribute_B: 	INFORM; NINFOM; NTXTDS; PICREP; SCAMAX; SCAMIN; TXTDSC;
+/// Set Attribute_C: 	RECDAT; RECIND; SORDAT; SORIND;
+///
+/// Definition:
+///     An isolated danger buoy is a buoy moored on or above an isolated danger of limited extent, which has navigable water all around it. (UKHO NP 735, 5th Edition)
+/// References
+///     INT 1:	IQ 130.4;
+///     S-4:	462.3;
+/// Remarks:
+///     Topmark, light, fog signal, radar reflector and retro-reflector are separate objects.
+/// Distinction:
+///     buoy cardinal; buoy installation; buoy lateral; buoy safe water; buoy special purpose/general;
+pub fn process_boyisd(geojson_geom: Geometry, properties: &mut JsonObject) -> Geometry {
+    // black with one or more broad horizontal red bands
+    let colours = vec![Colour::Black, Colour::Red, Colour::Black];
+    process_buoy("BOYISD", geojson_geom, properties, Some(Boyshp::Pillar), colours, vec![Colpat::HorizontalStripes])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+    use crate::test_util::point_symbol;
+
+    #[test]
+    fn test_process_boyisd() {
+        assert_eq!(Some(json!("BOYPIL72")), point_symbol(process_boyisd, json!({})));
+        assert_eq!(Some(json!("BOYSPR72")), point_symbol(process_boyisd, json!({"BOYSHP": 5})));
+        assert_eq!(Some(json!("BOYCAN76")), point_symbol(process_boyisd, json!({"BOYSHP": 2, "COLOUR": ["2", "3", "2"]})));
+    }
+}
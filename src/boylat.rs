@@ -0,0 +1,136 @@
+use crate::geojson_builder::JsonObject;
+use geojson::Geometry;
+use crate::boyspp::{Boyshp, process_buoy};
+use crate::colors::{Colpat, Colour};
+
+/// CATLAM
+///
+/// Expected input:
+///     ID	Meaning	INT 1	S-4
+///     1	port-hand lateral mark	IQ 130.1;	461.2;
+///     2	starboard-hand lateral mark	IQ 130.1;	461.2;
+///     3	preferred channel to starboard lateral mark	IQ 130.1;	461.3;
+///     4	preferred channel to port lateral mark	IQ 130.1;	461.3;
+/// Remarks:
+///     5 - 23 inland waterway (CEVNI) lateral marks are not distinguished
+pub enum Catlam {
+    PortHand,
+    StarboardHand,
+    PreferredChannelToStarboard,
+    PreferredChannelToPort,
+}
+
+impl Catlam {
+    pub fn from_value(properties: &JsonObject) -> Option<Catlam> {
+        properties.get("CATLAM").and_then(|value| {
+            value.as_i64()
+        }).and_then(|n| {
+            match n {
+                1_i64 => Some(Catlam::PortHand),
+                2_i64 => Some(Catlam::StarboardHand),
+                3_i64 => Some(Catlam::PreferredChannelToStarboard),
+                4_i64 => Some(Catlam::PreferredChannelToPort),
+                _ => None,
+            }
+        })
+    }
+
+    /// The marking colours of the IALA maritime buoyage system region, port-hand marks are red in
+    /// region A and green in region B
+    pub fn colours(&self, marsys: &Marsys) -> Vec<Colour> {
+        let (port, starboard) = match marsys {
+            Marsys::IalaB => (Colour::Green, Colour::Red),
+            _ => (Colour::Red, Colour::Green),
+        };
+        match self {
+            Catlam::PortHand => vec![port],
+            Catlam::StarboardHand => vec![starboard],
+            Catlam::PreferredChannelToStarboard => vec![port, starboard, port],
+            Catlam::PreferredChannelToPort => vec![starboard, port, starboard],
+        }
+    }
+
+    /// Port-hand marks are can shaped and starboard-hand marks are conical in both regions
+    pub fn shape(&self) -> Boyshp {
+        match self {
+            Catlam::PortHand | Catlam::PreferredChannelToStarboard => Boyshp::Can,
+            Catlam::StarboardHand | Catlam::PreferredChannelToPort => Boyshp::Conical,
+        }
+    }
+}
+
+/// MARSYS
+///
+/// Expected input:
+///     ID	Meaning	INT 1	S-4
+///     1	IALA A	IQ 130.1;	461.2;
+///     2	IALA B	IQ 130.1;	461.2;
+///     9	no system
+///     10	other system
+///     11	CEVNI
+///     12	Russian inland waterway regulations
+///     13	Brazilian national inland waterway regulations - two sides
+///     14	Brazilian national inland waterway regulations - side independent
+///     15	Paraguay-Parana waterway - Brazilian complementary aids to navigation
+/// Remarks:
+///     IALA A is assumed when the attribute is not encoded
+pub enum Marsys {
+    IalaA,
+    IalaB,
+    Other,
+}
+
+impl Marsys {
+    pub fn from_value(properties: &JsonObject) -> Marsys {
+        match properties.get("MARSYS").and_then(|value| value.as_i64()) {
+            None | Some(1) => Marsys::IalaA,
+            Some(2) => Marsys::IalaB,
+            Some(_) => Marsys::Other,
+        }
+    }
+}
+
+/// BOYLAT, Buoy Lateral
+/// Geometric primitives: Point
+///
+/// Set Attribute_A: 	(!)BOYSHP; (!)CATLAM; (!)COLOUR; (!?)COLPAT; CONRAD; DATEND; DATSTA; (?)MARSYS; NATCON; NOBJNM; OBJNAM; PEREND; PERSTA; STATUS; VERACC; VERLEN;
+/// Set Attribute_B: 	INFORM; NINFOM; NTXTDS; PICREP; SCAMAX; SCAMIN; TXTDSC;
+/// Set Attribute_C: 	RECDAT; RECIND; SORDAT; SORIND;
+///
+/// Definition:
+///     A lateral buoy is used to indicate the port or starboard hand side of the route to be followed. They are generally used for well defined channels and are used in conjunction with a conventional direction of buoyage. (UKHO NP 735, 5th Edition)
+/// References
+///     INT 1:	IQ 130.1;
+///     S-4:	461.2-3;
+/// Remarks:
+///     Topmark, light, fog signal, radar reflector and retro-reflector are separate objects.
+/// Distinction:
+///     buoy cardinal; buoy installation; buoy isolated danger; buoy safe water; buoy special purpose/general;
+pub fn process_boylat(geojson_geom: Geometry, properties: &mut JsonObject) -> Geometry {
+    let catlam = Catlam::from_value(properties);
+    let marsys = Marsys::from_value(properties);
+    let shape = catlam.as_ref().map(Catlam::shape);
+    let colours = catlam.as_ref().map(|c| c.colours(&marsys)).unwrap_or_default();
+    let pattern = if colours.len() > 1 { vec![Colpat::HorizontalStripes] } else { vec![] };
+    process_buoy("BOYLAT", geojson_geom, properties, shape, colours, pattern)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+    use crate::test_util::point_symbol;
+
+    #[test]
+    fn test_process_boylat() {
+        // Hylebos Waterway Lighted Buoy 1 of US5WA22M
+        assert_eq!(Some(json!("BOYPIL61")), point_symbol(process_boylat, json!({"BOYSHP": 4, "CATLAM": 1, "COLOUR": ["4"]})));
+        assert_eq!(Some(json!("BOYPIL60")), point_symbol(process_boylat, json!({"BOYSHP": 4, "CATLAM": 2, "COLOUR": ["3"]})));
+        // colours and shape of the buoyage region when they are not encoded
+        assert_eq!(Some(json!("BOYCAN60")), point_symbol(process_boylat, json!({"CATLAM": 1})));
+        assert_eq!(Some(json!("BOYCAN61")), point_symbol(process_boylat, json!({"CATLAM": 1, "MARSYS": 2})));
+        assert_eq!(Some(json!("BOYCON61")), point_symbol(process_boylat, json!({"CATLAM": 2, "MARSYS": 1})));
+        assert_eq!(Some(json!("BOYCAN72")), point_symbol(process_boylat, json!({"CATLAM": 3, "BOYSHP": 2})));
+        assert_eq!(Some(json!("BOYGEN03")), point_symbol(process_boylat, json!({})));
+    }
+}
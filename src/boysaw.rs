@@ -0,0 +1,40 @@
+use crate::geojson_builder::JsonObject;
+use geojson::Geometry;
+use crate::boyspp::{Boyshp, process_buoy};
+use crate::colors::{Colpat, Colour};
+
+/// BOYSAW, Buoy Safe Water
+/// Geometric primitives: Point
+///
+/// Set Attribute_A: 	(!)BOYSHP; (!)COLOUR; (!?)COLPAT; CONRAD; DATEND; DATSTA; MARSYS; NATCON; NOBJNM; OBJNAM; PEREND; PERSTA; STATUS; VERACC; VERLEN;
+/// Set Attribute_B: 	INFORM; NINFOM; NTXTDS; PICREP; SCAMAX; SCAMIN; TXTDSC;
+/// Set Attribute_C: 	RECDAT; RECIND; SORDAT; SORIND;
+///
+/// Definition:
+///     A safe water buoy is used to indicate that there is navigable water around the mark. (UKHO NP 735, 5th Edition)
+/// References
+///     INT 1:	IQ 130.5;
+///     S-4:	463.1;
+/// Remarks:
+///     Topmark, light, fog signal, radar reflector and retro-reflector are separate objects.
+/// Distinction:
+///     buoy cardinal; buoy installation; buoy isolated danger; buoy lateral; buoy special purpose/general;
+pub fn process_boysaw(geojson_geom: Geometry, properties: &mut JsonObject) -> Geometry {
+    // red and white vertical stripes
+    let colours = vec![Colour::Red, Colour::White];
+    process_buoy("BOYSAW", geojson_geom, properties, Some(Boyshp::Spherical), colours, vec![Colpat::VerticalStripes])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+    use crate::test_util::point_symbol;
+
+    #[test]
+    fn test_process_boysaw() {
+        assert_eq!(Some(json!("BOYSPH65")), point_symbol(process_boysaw, json!({})));
+        assert_eq!(Some(json!("BOYPIL73")), point_symbol(process_boysaw, json!({"BOYSHP": 4})));
+        assert_eq!(Some(json!("BOYSPR65")), point_symbol(process_boysaw, json!({"BOYSHP": 5})));
+    }
+}
@@ -1,9 +1,9 @@
-use serde_json::json;
-use serde_json::Value;
+use serde_json::{Value, json};
 use crate::geojson_builder::JsonObject;
 use geojson::Geometry;
 use crate::colors::{Colpat, Colour};
 use crate::catspm::Catspm;
+use crate::lookups;
 use crate::util::compare;
 
 /// BOYSHP
@@ -45,6 +45,46 @@ impl Boyshp {
             }
         })
     }
+
+    /// The S-57 attribute value ID
+    pub fn value(&self) -> i64 {
+        match self {
+            Boyshp::Conical => 1,
+            Boyshp::Can => 2,
+            Boyshp::Spherical => 3,
+            Boyshp::Pillar => 4,
+            Boyshp::Spar => 5,
+            Boyshp::Barrel => 6,
+            Boyshp::SuperBuoy => 7,
+            Boyshp::IceBuoy => 8,
+        }
+    }
+}
+
+/// Assigns the symbol `SY` of a buoy from the S-52 look-up table of the class. The shape, colours
+/// and colour pattern the buoy's category prescribes are used when they are not encoded.
+pub fn process_buoy(
+    class: &str,
+    geojson_geom: Geometry,
+    properties: &mut JsonObject,
+    shape: Option<Boyshp>,
+    colours: Vec<Colour>,
+    pattern: Vec<Colpat>,
+) -> Geometry {
+    let mut attributes = properties.clone();
+    if let (None, Some(shape)) = (Boyshp::from_value(properties), shape) {
+        attributes.insert(String::from("BOYSHP"), json!(shape.value()));
+    }
+    if Colour::from_value(properties).is_empty() && !colours.is_empty() {
+        attributes.insert(String::from("COLOUR"), json!(colours.iter().map(Colour::value).collect::<Vec<_>>()));
+    }
+    if Colpat::from_value(properties).is_empty() && !pattern.is_empty() {
+        attributes.insert(String::from("COLPAT"), json!(pattern.iter().map(Colpat::value).collect::<Vec<_>>()));
+    }
+    if let Some(symbol) = lookups::symbol(class, &geojson_geom.value, &attributes) {
+        properties.insert(String::from("SY"), Value::String(symbol));
+    }
+    geojson_geom
 }
 
 /// BOYSPP, Buoy Special Purpose / General
@@ -65,25 +105,6 @@ impl Boyshp {
 ///     Topmark, light, fog signal, radar reflector and retro-reflector are separate objects.
 /// Distinction:
 ///     buoy lateral; buoy safe water; buoy isolated danger; buoy cardinal; buoy installation; mooring/warping facility;
-pub fn layers() -> Vec<Value> {
-    vec![
-        json!({
-            "id": "BOYSPP_point",
-            "type": "symbol",
-            "source": "src_senc",
-            "source-layer": "BOYSPP",
-            "filter": [ "any", [ "==", "$type", "Point" ] ],
-            "layout": {
-                "icon-image": ["get", "SY"],
-                "icon-anchor": "bottom",
-                "icon-allow-overlap": true,
-                "icon-keep-upright": true,
-                "symbol-placement": "point"
-            }
-        })
-    ]
-}
-
 pub fn process_boyspp(geojson_geom: Geometry, properties: &mut JsonObject) -> Geometry {
     Boyshp::from_value(properties).map(|shape| {
         let pattern = Colpat::from_value(properties);
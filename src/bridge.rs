@@ -0,0 +1,225 @@
+use geojson::Value as GeoValue;
+use serde_json::{Value, json};
+use crate::geojson_builder::JsonObject;
+use crate::s57::ChartLayer;
+use crate::{csp, resare};
+
+/// Structures spanning a waterway with a vertical or horizontal clearance
+const OVERHEAD: [&str; 4] = ["BRIDGE", "CBLOHD", "CONVYR", "PIPOHD"];
+
+/// The clearance description with heights in meters and in feet eg `clr cl 5.2m`
+static CLRDSN: &str = "CLRDSN";
+static CLRDSN_FT: &str = "CLRDSN_FT";
+
+/// CATBRG of opening bridges
+///     2 opening bridge, 3 swing bridge, 4 lifting bridge, 5 bascule bridge, 7 draw bridge,
+///     8 transporter bridge
+const OPENING: [i64; 6] = [2, 3, 4, 5, 7, 8];
+
+/// BRIDGE, Bridge
+/// Geometric primitives: Point, Line, Area
+///
+/// Set Attribute_A: 	(!)CATBRG; COLOUR; COLPAT; CONDTN; CONRAD; CONVIS; DATEND; DATSTA; HORACC; HORCLR; NATCON; NOBJNM; OBJNAM; VERACC; VERCCL; VERCLR; VERCOP; VERDAT;
+/// Set Attribute_B: 	INFORM; NINFOM; NTXTDS; PICREP; SCAMAX; SCAMIN; TXTDSC;
+/// Set Attribute_C: 	RECDAT; RECIND; SORDAT; SORIND;
+///
+/// Definition:
+///     A structure erected over a depression or an obstacle such as a body of water, railroad, etc. to provide a roadway for vehicles, pedestrians or to carry utility services. (IHO Dictionary, S-32, 5th Edition, 552)
+/// References
+///     INT 1:	ID 22-27;
+///     S-4:	381.1-4;
+/// Remarks:
+///     An opening bridge is encoded with its clearances when closed (VERCCL) and open (VERCOP),
+///     a fixed bridge with its vertical clearance (VERCLR). Clearances are converted from the
+///     units of height HUNI of the cell to meters when the cell is read.
+/// Distinction:
+///     cable, overhead; conveyor; pipeline, overhead; pylon/bridge support;
+///
+/// Adds a point at the middle of the span of every bridge, overhead cable, conveyor and overhead
+/// pipeline carrying the clearance descriptions `CLRDSN` and `CLRDSN_FT`. The symbol `SY` and
+/// name are moved from the span to the point, opening bridges are drawn with BRIDGE01. Point
+/// structures are labelled where they are.
+pub fn clearance_labels(layers: &mut [ChartLayer]) {
+    for layer in layers.iter_mut().filter(|l| OVERHEAD.contains(&l.name.as_str())) {
+        let mut points = vec![];
+        for feature in layer.collection.features.iter_mut() {
+            if let (Some(geometry), Some(properties)) = (&feature.geometry, &mut feature.properties) {
+                let opening = csp::list_values(properties, "CATBRG").iter().any(|c| OPENING.contains(c));
+                if opening && !properties.contains_key("SY") {
+                    properties.insert(String::from("SY"), json!("BRIDGE01"));
+                }
+                let meters = clearance_description(properties, false);
+                let feet = clearance_description(properties, true);
+                if meters.is_none() && !properties.contains_key("SY") && !properties.contains_key("TX") {
+                    continue;
+                }
+                if let GeoValue::Point(_) = geometry.value {
+                    label_clearances(properties, meters, feet);
+                    continue;
+                }
+                let point = match span_midpoint(&geometry.value) {
+                    Some(point) => point,
+                    None => continue,
+                };
+                let mut label = properties.clone();
+                for key in resare::LABEL_PROPERTIES.iter() {
+                    properties.remove(*key);
+                }
+                label_clearances(&mut label, meters, feet);
+                points.push(geojson::Feature {
+                    bbox: None,
+                    geometry: Some(geojson::Geometry::new(GeoValue::Point(point))),
+                    id: None,
+                    properties: Some(label),
+                    foreign_members: None,
+                });
+            }
+        }
+        layer.collection.features.extend(points);
+    }
+}
+
+/// Names the label `TX` after the structure and adds the clearance descriptions
+fn label_clearances(label: &mut JsonObject, meters: Option<String>, feet: Option<String>) {
+    // the look-up table's text repeats the clearances in meters only
+    label.remove("TX");
+    if let Some(name) = label.get("OBJNAM").and_then(|v| v.as_str()).map(String::from) {
+        label.insert(String::from("TX"), json!(name));
+        label.insert(String::from("TXCL"), json!("CHBLK"));
+    }
+    if let (Some(meters), Some(feet)) = (meters, feet) {
+        label.insert(String::from(CLRDSN), json!(meters));
+        label.insert(String::from(CLRDSN_FT), json!(feet));
+    }
+}
+
+/// The clearances of the structure, one per line
+///     VERCLR - vertical clearance, `clr`
+///     VERCCL - vertical clearance closed, `clr cl`
+///     VERCOP - vertical clearance open, `clr op`
+///     VERCSA - safe vertical clearance of an overhead cable, `sf clr`
+///     HORCLR - horizontal clearance, `hor clr`
+pub fn clearance_description(properties: &JsonObject, feet: bool) -> Option<String> {
+    let lines: Vec<String> = [
+        ("VERCLR", "clr"), ("VERCCL", "clr cl"), ("VERCOP", "clr op"), ("VERCSA", "sf clr"), ("HORCLR", "hor clr"),
+    ].iter()
+        .filter_map(|(key, label)| {
+            let meters = properties.get(*key).and_then(|v| v.as_f64().or_else(|| v.as_str().and_then(|s| s.parse().ok())))?;
+            if feet {
+                Some(format!("{} {}ft", label, (meters / 0.3048).round() as i64))
+            } else {
+                Some(format!("{} {:.1}m", label, meters))
+            }
+        })
+        .collect();
+    if lines.is_empty() {
+        None
+    } else {
+        Some(lines.join("\n"))
+    }
+}
+
+/// The point halfway along the longest line of the span, or a point inside an area
+fn span_midpoint(geometry: &GeoValue) -> Option<Vec<f64>> {
+    let line = match geometry {
+        GeoValue::LineString(line) => line,
+        GeoValue::MultiLineString(lines) => lines.iter()
+            .max_by(|a, b| length(a).partial_cmp(&length(b)).unwrap())?,
+        GeoValue::Polygon(_) | GeoValue::MultiPolygon(_) => return resare::label_point(geometry),
+        _ => return None,
+    };
+    let mut remaining = length(line) / 2.0;
+    for segment in line.windows(2) {
+        let d = distance(&segment[0], &segment[1]);
+        if d >= remaining && d > 0.0 {
+            let t = remaining / d;
+            return Some(vec![
+                segment[0][0] + t * (segment[1][0] - segment[0][0]),
+                segment[0][1] + t * (segment[1][1] - segment[0][1]),
+            ]);
+        }
+        remaining -= d;
+    }
+    line.first().cloned()
+}
+
+fn distance(a: &[f64], b: &[f64]) -> f64 {
+    ((b[0] - a[0]).powi(2) + (b[1] - a[1]).powi(2)).sqrt()
+}
+
+fn length(line: &[Vec<f64>]) -> f64 {
+    line.windows(2).map(|s| distance(&s[0], &s[1])).sum()
+}
+
+/// Clearance labels of the overhead structures drawn above their label points, heights are
+/// described in feet on feet and fathoms styles
+pub fn layers(colors: &Value, depth: &str) -> Vec<Value> {
+    let description = match depth {
+        "meters" => CLRDSN,
+        _ => CLRDSN_FT,
+    };
+    OVERHEAD.iter().map(|class| json!({
+        "id": format!("{}_clearance", class),
+        "type": "symbol",
+        "source": "src_senc",
+        "source-layer": class,
+        "filter": ["all", ["==", "$type", "Point"], ["has", description]],
+        "layout": {
+            "text-font": ["Roboto Bold"],
+            "text-field": ["get", description],
+            "text-size": 10,
+            "text-anchor": "bottom",
+            "text-offset": [0, -0.8],
+            "text-allow-overlap": false,
+            "symbol-placement": "point"
+        },
+        "paint": {
+            "text-color": colors["CHBLK"],
+            "text-halo-color": *crate::colors::TXT_FG,
+            "text-halo-width": 1
+        }
+    })).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::chart_layer;
+
+    fn properties(value: Value) -> JsonObject {
+        value.as_object().unwrap().clone()
+    }
+
+    #[test]
+    fn test_clearance_description() {
+        let fixed = properties(json!({"VERCLR": 12.3, "HORCLR": "30"}));
+        assert_eq!(Some(String::from("clr 12.3m\nhor clr 30.0m")), clearance_description(&fixed, false));
+        assert_eq!(Some(String::from("clr 40ft\nhor clr 98ft")), clearance_description(&fixed, true));
+        assert_eq!(None, clearance_description(&properties(json!({})), false));
+    }
+
+    #[test]
+    fn test_clearance_labels() {
+        let mut layers = vec![chart_layer("BRIDGE", vec![
+            (
+                GeoValue::LineString(vec![vec![0.0, 0.0], vec![1.0, 0.0], vec![1.0, 2.0]]),
+                json!({"CATBRG": ["3"], "OBJNAM": "Foo", "VERCCL": 5.2, "VERCOP": 40.0, "TX": "Foo\nclr cl  5.2", "LSTY": "SOLD"}),
+            ),
+            (GeoValue::Point(vec![2.0, 2.0]), json!({"CATBRG": ["1"], "VERCLR": 12.3, "SY": "BRIDGE01"})),
+        ])];
+        clearance_labels(&mut layers);
+        let features = &layers[0].collection.features;
+        // a point bridge is labelled where it is
+        assert_eq!(3, features.len());
+        assert!(features[0].properties.as_ref().unwrap().get("TX").is_none());
+        let point = features[1].properties.as_ref().unwrap();
+        assert_eq!(json!("BRIDGE01"), point["SY"]);
+        assert_eq!(json!("clr 12.3m"), point[CLRDSN]);
+        let label = features[2].properties.as_ref().unwrap();
+        assert_eq!(json!("BRIDGE01"), label["SY"]);
+        assert_eq!(json!("Foo"), label["TX"]);
+        assert_eq!(json!("clr cl 5.2m\nclr op 40.0m"), label[CLRDSN]);
+        assert_eq!(json!("clr cl 17ft\nclr op 131ft"), label[CLRDSN_FT]);
+        assert_eq!(Some(geojson::Geometry::new(GeoValue::Point(vec![1.0, 0.5]))), features[2].geometry);
+    }
+}
@@ -0,0 +1,146 @@
+use std::path::{Path, PathBuf};
+use crate::iso8211::Iso8211File;
+
+/// An ENC base cell (.000) and the update files (.001 - .nnn) listed for it
+pub struct EncCell {
+    pub path: PathBuf,
+    pub updates: Vec<PathBuf>,
+}
+
+impl EncCell {
+    pub fn name(&self) -> String {
+        self.path.file_stem().and_then(|s| s.to_str()).unwrap_or("").to_string()
+    }
+}
+
+/// CATD, Catalogue Directory field
+///
+/// Subfields: RCNM; RCID; FILE; LFIL; VOLM; IMPL; SLAT; WLON; NLAT; ELON; CRCS; COMT;
+///
+/// Definition:
+///     Each file of an exchange set is listed in the catalogue file CATALOG.031 found in the
+///     ENC_ROOT directory. FILE is the path of the file relative to ENC_ROOT using '\' separators
+///     and IMPL is the implementation, BIN for base cells and updates, ASC for text and TIF for
+///     picture files.
+/// References
+///     S-57: Part 3, 7.4.1; Appendix B.1, 5.4
+pub struct CatalogEntry {
+    pub file: String,
+    pub implementation: String,
+}
+
+pub struct Catalog {
+    root: PathBuf,
+    pub entries: Vec<CatalogEntry>,
+}
+
+impl Catalog {
+    pub fn is_catalog(path: &Path) -> bool {
+        path.file_name()
+            .and_then(|n| n.to_str())
+            .map(|n| n.eq_ignore_ascii_case("CATALOG.031"))
+            .unwrap_or(false)
+    }
+
+    pub fn open(path: &Path) -> Option<Catalog> {
+        let root = path.parent()?.to_path_buf();
+        let file = Iso8211File::open(path)?;
+        let entries = file.records.iter()
+            .filter_map(|r| file.subfields(r, "CATD"))
+            .map(|catd| CatalogEntry {
+                file: catd.get("FILE").cloned().unwrap_or_default(),
+                implementation: catd.get("IMPL").cloned().unwrap_or_default(),
+            })
+            .filter(|e| !e.file.is_empty())
+            .collect();
+        Some(Catalog { root, entries })
+    }
+
+    /// The exchange set name, usually the directory containing ENC_ROOT
+    pub fn name(&self) -> String {
+        let root_name = self.root.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        if root_name.eq_ignore_ascii_case("ENC_ROOT") {
+            self.root.parent().and_then(|p| p.file_name()).and_then(|n| n.to_str()).unwrap_or(root_name)
+        } else {
+            root_name
+        }.to_string()
+    }
+
+    /// Every base cell listed in the catalogue with its updates in sequence
+    pub fn cells(&self) -> Vec<EncCell> {
+        let mut cells: Vec<EncCell> = self.entries.iter()
+            .filter(|e| e.implementation == "BIN" && update_number(&e.file) == Some(0))
+            .map(|e| EncCell { path: self.resolve(&e.file), updates: vec![] })
+            .collect();
+        let mut updates: Vec<(u32, PathBuf)> = self.entries.iter()
+            .filter(|e| e.implementation == "BIN")
+            .filter_map(|e| update_number(&e.file).filter(|n| *n > 0).map(|n| (n, self.resolve(&e.file))))
+            .collect();
+        updates.sort();
+        for (_, update) in updates {
+            let stem = update.file_stem().map(|s| s.to_os_string());
+            match cells.iter_mut().find(|c| c.path.file_stem().map(|s| s.to_os_string()) == stem) {
+                Some(cell) => cell.updates.push(update),
+                None => println!("update without a base cell: {:?}", update),
+            }
+        }
+        cells
+    }
+
+    fn resolve(&self, file: &str) -> PathBuf {
+        file.split(|c| c == '\\' || c == '/').fold(self.root.clone(), |path, part| path.join(part))
+    }
+}
+
+/// The numeric extension of a cell file eg 0 for US5WA22M.000 and 3 for US5WA22M.003
+fn update_number(file: &str) -> Option<u32> {
+    let extension = file.rsplit('.').next()?;
+    if extension.len() == 3 && file.contains('.') {
+        extension.parse().ok()
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::iso8211::test::{record, field_definition};
+    use std::fs;
+
+    fn catd(rcid: u32, file: &str, implementation: &str) -> (&'static str, Vec<u8>) {
+        ("CATD", format!("CD{:010}{}\x1f\x1fV01X01\x1f{}\x1f\x1f\x1f\x1f\x1f\x1f\x1e", rcid, file, implementation).into_bytes())
+    }
+
+    #[test]
+    fn test_catalog_cells() {
+        let mut bytes = record('L', &[
+            ("0001", field_definition("ISO 8211 Record Identifier", "", "")),
+            ("CATD", field_definition(
+                "Catalogue Directory",
+                "RCNM!RCID!FILE!LFIL!VOLM!IMPL!SLAT!WLON!NLAT!ELON!CRCS!COMT",
+                "(A(2),I(10),3A,A(3),4R,2A)",
+            )),
+        ]);
+        bytes.extend(record('D', &[catd(1, "CATALOG.031", "ASC")]));
+        bytes.extend(record('D', &[catd(2, "US5WA22M\\US5WA22M.002", "BIN")]));
+        bytes.extend(record('D', &[catd(3, "US5WA22M\\US5WA22M.000", "BIN")]));
+        bytes.extend(record('D', &[catd(4, "US5WA22M\\US5WA22A.TXT", "ASC")]));
+        bytes.extend(record('D', &[catd(5, "US5WA22M\\US5WA22M.001", "BIN")]));
+        let dir = std::env::temp_dir().join("s57tiler_test").join("ENC_ROOT");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("CATALOG.031");
+        fs::write(&path, bytes).unwrap();
+
+        assert!(Catalog::is_catalog(&path));
+        let catalog = Catalog::open(&path).unwrap();
+        assert_eq!(5, catalog.entries.len());
+        assert_eq!("s57tiler_test", catalog.name());
+        let cells = catalog.cells();
+        assert_eq!(1, cells.len());
+        assert_eq!("US5WA22M", cells[0].name());
+        assert_eq!(dir.join("US5WA22M").join("US5WA22M.000"), cells[0].path);
+        assert_eq!(vec![dir.join("US5WA22M").join("US5WA22M.001"), dir.join("US5WA22M").join("US5WA22M.002")], cells[0].updates);
+        fs::remove_file(&path).ok();
+    }
+}
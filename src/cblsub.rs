@@ -0,0 +1,55 @@
+use serde_json::{Value, json};
+
+/// Cable and pipeline areas hatched over their extent
+const HATCHED: [&str; 2] = ["CBLARE", "PIPARE"];
+
+/// The hatch pattern of the sprite sheet, diagonal lines at 45 degrees
+static HATCH: &str = "HATCH01";
+
+/// CBLARE, Cable area
+/// Geometric primitives: Area
+///
+/// Set Attribute_A: 	CATCBL; DATEND; DATSTA; NOBJNM; OBJNAM; RESTRN; STATUS;
+/// Set Attribute_B: 	INFORM; NINFOM; NTXTDS; PICREP; SCAMAX; SCAMIN; TXTDSC;
+/// Set Attribute_C: 	RECDAT; RECIND; SORDAT; SORIND;
+///
+/// Definition:
+///     An area containing one or more submarine or buried cables. (IHO Dictionary, S-32, 5th Edition, 616)
+/// References
+///     INT 1:	IL 30.1, 31.1;
+///     S-4:	439.1-4; 443.3;
+/// Remarks:
+///     The boundary is drawn as a dashed magenta line with T-marks (CBLARE51), pipeline areas
+///     (PIPARE) as a magenta or grey line with T-marks (PIPARE51, PIPARE61). The prohibition of
+///     anchoring and fishing is given by RESTRN.
+/// Distinction:
+///     cable, submarine; cable, overhead; pipeline area; restricted area;
+///
+/// Hatch layers of the cable and pipeline areas over the area's fill. The hatch is a repeated
+/// pattern so its lines are at 45 degrees on screen and equally spaced at every zoom.
+pub fn layers() -> Vec<Value> {
+    HATCHED.iter().map(|class| json!({
+        "id": format!("{}_hatch", class),
+        "type": "fill",
+        "source": "src_senc",
+        "source-layer": class,
+        "filter": ["==", "$type", "Polygon"],
+        "paint": {
+            "fill-pattern": HATCH,
+            "fill-opacity": 0.5
+        }
+    })).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_layers() {
+        let layers = layers();
+        assert_eq!(vec!["CBLARE_hatch", "PIPARE_hatch"], layers.iter().map(|l| l["id"].as_str().unwrap()).collect::<Vec<&str>>());
+        assert!(crate::sprites::has_symbol(layers[0]["paint"]["fill-pattern"].as_str().unwrap()));
+        assert!(crate::sprites::has_symbol("CBLSUB06"));
+    }
+}
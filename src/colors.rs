@@ -17,7 +17,7 @@ use crate::geojson_builder::JsonObject;
 ///     11	orange	IP 11.7;	450.2-3;
 ///     12	magenta
 ///     13	pink
-#[derive(Eq, PartialEq, Hash)]
+#[derive(Eq, PartialEq, Hash, Clone, Copy)]
 pub enum Colour {
     White,
     Black,
@@ -68,6 +68,25 @@ impl Colour {
             }).filter(|ea| ea.is_some()).map(|ea| ea.unwrap()).collect::<Vec<Colour>>()
         }).unwrap_or(vec![])
     }
+
+    /// The S-57 attribute value ID
+    pub fn value(&self) -> &'static str {
+        match self {
+            Colour::White => "1",
+            Colour::Black => "2",
+            Colour::Red => "3",
+            Colour::Green => "4",
+            Colour::Blue => "5",
+            Colour::Yellow => "6",
+            Colour::Grey => "7",
+            Colour::Brown => "8",
+            Colour::Amber => "9",
+            Colour::Violet => "10",
+            Colour::Orange => "11",
+            Colour::Magenta => "12",
+            Colour::Pink => "13",
+        }
+    }
 }
 
 /// COLPAT Attribute
@@ -115,6 +134,18 @@ impl Colpat {
             }).filter(|ea| ea.is_some()).map(|ea| ea.unwrap()).collect::<Vec<Colpat>>()
         }).unwrap_or(vec![])
     }
+
+    /// The S-57 attribute value ID
+    pub fn value(&self) -> &'static str {
+        match self {
+            Colpat::HorizontalStripes => "1",
+            Colpat::VerticalStripes => "2",
+            Colpat::DiagonalStripes => "3",
+            Colpat::Squared => "4",
+            Colpat::Stripes => "5",
+            Colpat::BorderStripe => "6",
+        }
+    }
 }
 
 const COLORS_JSON: &'static [u8] = include_bytes!("colors.json");
@@ -0,0 +1,390 @@
+use geo_booleanop::boolean::BooleanOp;
+use geo_types::{Coordinate, LineString, MultiPolygon, Polygon};
+use geojson::{Feature, Value};
+use serde_json::json;
+use crate::s57::ChartLayer;
+
+/// A cell of an exchange set with the DSID / DSPM information needed to composite it
+pub struct Cell {
+    pub name: String,
+    /// DSID_INTU intended usage (navigational purpose)
+    pub intended_usage: Option<u8>,
+    /// DSPM_CSCL compilation scale denominator
+    pub scale: Option<u32>,
+    /// M_COVR coverage polygons where CATCOV = 1 (coverage available)
+    pub coverage: Vec<Value>,
+    pub layers: Vec<ChartLayer>,
+}
+
+impl Cell {
+    /// INTU
+    ///     ID	Meaning	    compilation scale
+    ///     1	Overview	< 1:1,500,000
+    ///     2	General	    1:350,000 - 1:1,500,000
+    ///     3	Coastal	    1:90,000 - 1:350,000
+    ///     4	Approach	1:22,000 - 1:90,000
+    ///     5	Harbour	    1:4,000 - 1:22,000
+    ///     6	Berthing	> 1:4,000
+    ///
+    /// The navigational purpose band, derived from the compilation scale when INTU is not encoded
+    pub fn band(&self) -> u8 {
+        match (self.intended_usage, self.scale) {
+            (Some(intu), _) if (1..=6).contains(&intu) => intu,
+            (_, Some(scale)) if scale > 1_500_000 => 1,
+            (_, Some(scale)) if scale > 350_000 => 2,
+            (_, Some(scale)) if scale > 90_000 => 3,
+            (_, Some(scale)) if scale > 22_000 => 4,
+            (_, Some(scale)) if scale > 4_000 => 5,
+            (_, Some(_)) => 6,
+            _ => 1,
+        }
+    }
+}
+
+/// The zoom level where the scale band becomes the best available data, this is roughly where
+/// the web mercator display scale at mid latitudes reaches the band's smallest compilation scale.
+fn band_min_zoom(band: u8) -> u8 {
+    match band {
+        2 => 8,
+        3 => 10,
+        4 => 12,
+        5 => 14,
+        6 => 16,
+        _ => 0,
+    }
+}
+
+/// Composites overlapping cells so the best scale data wins at every zoom level. Each cell is
+/// shown from its band's zoom level (the lowest band present from `min_zoom`) to `max_zoom`, and
+/// from the zoom level where a higher band cell is shown, the cell's features are clipped against
+/// the higher band's coverage. Features are given `tippecanoe` minzoom / maxzoom members.
+pub fn composite(cells: Vec<Cell>, min_zoom: u8, max_zoom: u8) -> Vec<Vec<ChartLayer>> {
+    let lowest_band = cells.iter().map(|c| c.band()).min().unwrap_or(1);
+    let start_zoom = |band: u8| {
+        if band == lowest_band {
+            min_zoom
+        } else {
+            band_min_zoom(band).max(min_zoom).min(max_zoom)
+        }
+    };
+    let coverage: Vec<(u8, MultiPolygon<f64>)> = cells.iter()
+        .map(|c| (c.band(), to_multi_polygon(&c.coverage)))
+        .collect();
+
+    cells.into_iter().map(|cell| {
+        let band = cell.band();
+        let cell_start = start_zoom(band);
+        let mut breaks: Vec<u8> = coverage.iter()
+            .filter(|(b, _)| *b > band)
+            .map(|(b, _)| start_zoom(*b))
+            .filter(|z| *z > cell_start)
+            .collect();
+        breaks.sort();
+        breaks.dedup();
+        let mut starts = vec![cell_start];
+        starts.extend(breaks);
+
+        // zoom range and the coverage of higher bands shown in that range
+        let segments: Vec<(u8, u8, MultiPolygon<f64>)> = starts.iter().enumerate().map(|(i, start)| {
+            let end = starts.get(i + 1).map(|next| next - 1).unwrap_or(max_zoom);
+            let mask = coverage.iter()
+                .filter(|(b, _)| *b > band && start_zoom(*b) <= *start)
+                .fold(MultiPolygon(vec![]), |acc, (_, c)| if acc.0.is_empty() { c.clone() } else { acc.union(c) });
+            (*start, end, mask)
+        }).collect();
+        println!("compositing cell {} band {} zoom ranges {:?}", cell.name, band,
+                 segments.iter().map(|s| (s.0, s.1)).collect::<Vec<(u8, u8)>>());
+
+        cell.layers.into_iter().map(|layer| {
+            let mut features: Vec<Feature> = vec![];
+            for feature in layer.collection.features {
+                let mut ranges: Vec<(u8, u8, Option<Value>)> = vec![];
+                for (start, end, mask) in &segments {
+                    let value = feature.geometry.as_ref().and_then(|g| subtract(&g.value, mask));
+                    match ranges.last_mut() {
+                        Some(last) if last.2 == value && last.1 + 1 == *start => last.1 = *end,
+                        _ => ranges.push((*start, *end, value)),
+                    }
+                }
+                for (start, end, value) in ranges {
+                    if let Some(value) = value {
+                        let mut copy = feature.clone();
+                        copy.geometry = Some(geojson::Geometry::new(value));
+                        if set_zoom_range(&mut copy, start, end) {
+                            features.push(copy);
+                        }
+                    }
+                }
+            }
+            ChartLayer {
+                name: layer.name,
+                fields: layer.fields,
+                collection: geojson::FeatureCollection {
+                    bbox: None,
+                    features,
+                    foreign_members: None,
+                },
+            }
+        }).collect()
+    }).collect()
+}
+
+/// Sets the tippecanoe zoom range of the feature, honored by the tiler, to the intersection of
+/// the range with the feature's own range eg the zoom of a light sector. False when they do not
+/// overlap and the feature is not drawn at any zoom.
+fn set_zoom_range(feature: &mut Feature, min_zoom: u8, max_zoom: u8) -> bool {
+    let members = feature.foreign_members.get_or_insert_with(Default::default);
+    let zoom = |key: &str| members.get("tippecanoe").and_then(|t| t.get(key)).and_then(|z| z.as_u64());
+    let min_zoom = zoom("minzoom").map(|z| (z as u8).max(min_zoom)).unwrap_or(min_zoom);
+    let max_zoom = zoom("maxzoom").map(|z| (z as u8).min(max_zoom)).unwrap_or(max_zoom);
+    members.insert(String::from("tippecanoe"), json!({ "minzoom": min_zoom, "maxzoom": max_zoom }));
+    min_zoom <= max_zoom
+}
+
+/// The part of the geometry outside of the mask, None when nothing remains
+fn subtract(value: &Value, mask: &MultiPolygon<f64>) -> Option<Value> {
+    if mask.0.is_empty() {
+        return Some(value.clone());
+    }
+    let outside = |p: &Vec<f64>| !contains(mask, (p[0], p[1]));
+    let result = match value {
+        Value::Point(p) => {
+            if outside(p) {
+                Value::Point(p.clone())
+            } else {
+                return None;
+            }
+        }
+        Value::MultiPoint(mp) => Value::MultiPoint(mp.iter().filter(|p| outside(p)).cloned().collect()),
+        Value::LineString(l) => Value::MultiLineString(subtract_line(l, mask)),
+        Value::MultiLineString(ml) => Value::MultiLineString(ml.iter().flat_map(|l| subtract_line(l, mask)).collect()),
+        Value::Polygon(_) | Value::MultiPolygon(_) => {
+            let difference = to_multi_polygon(std::slice::from_ref(value)).difference(mask);
+            Value::MultiPolygon(difference.0.iter().map(from_polygon).collect())
+        }
+        Value::GeometryCollection(_) => value.clone(),
+    };
+    let is_empty = match &result {
+        Value::MultiPoint(mp) => mp.is_empty(),
+        Value::MultiLineString(ml) => ml.is_empty(),
+        Value::MultiPolygon(mp) => mp.is_empty(),
+        _ => false,
+    };
+    if is_empty {
+        None
+    } else {
+        Some(simplify(result))
+    }
+}
+
+/// Collapses single part multi geometries back into their simple type
+fn simplify(value: Value) -> Value {
+    match value {
+        Value::MultiLineString(mut ml) if ml.len() == 1 => Value::LineString(ml.remove(0)),
+        Value::MultiPolygon(mut mp) if mp.len() == 1 => Value::Polygon(mp.remove(0)),
+        _ => value,
+    }
+}
+
+/// Splits the line where it crosses the mask boundary and keeps the parts outside of the mask
+fn subtract_line(line: &[Vec<f64>], mask: &MultiPolygon<f64>) -> Vec<Vec<Vec<f64>>> {
+    let edges: Vec<((f64, f64), (f64, f64))> = mask.0.iter()
+        .flat_map(|p| std::iter::once(p.exterior()).chain(p.interiors().iter()))
+        .flat_map(|ring| ring.0.windows(2).map(|w| ((w[0].x, w[0].y), (w[1].x, w[1].y))).collect::<Vec<_>>())
+        .collect();
+    let mut parts: Vec<Vec<Vec<f64>>> = vec![];
+    let mut current: Vec<Vec<f64>> = vec![];
+    for seg in line.windows(2) {
+        let a = (seg[0][0], seg[0][1]);
+        let b = (seg[1][0], seg[1][1]);
+        let mut ts: Vec<f64> = edges.iter().filter_map(|e| intersection(a, b, e.0, e.1)).collect();
+        ts.push(0.0);
+        ts.push(1.0);
+        ts.sort_by(|x, y| x.partial_cmp(y).unwrap());
+        ts.dedup();
+        let at = |t: f64| vec![a.0 + t * (b.0 - a.0), a.1 + t * (b.1 - a.1)];
+        for w in ts.windows(2) {
+            let mid = at((w[0] + w[1]) / 2.0);
+            if contains(mask, (mid[0], mid[1])) {
+                if current.len() >= 2 {
+                    parts.push(current);
+                }
+                current = vec![];
+            } else {
+                let start = at(w[0]);
+                if current.last() != Some(&start) {
+                    if current.len() >= 2 {
+                        parts.push(current);
+                    }
+                    current = vec![start];
+                }
+                current.push(at(w[1]));
+            }
+        }
+    }
+    if current.len() >= 2 {
+        parts.push(current);
+    }
+    parts
+}
+
+/// The parameter t along a-b where it intersects c-d
+fn intersection(a: (f64, f64), b: (f64, f64), c: (f64, f64), d: (f64, f64)) -> Option<f64> {
+    let r = (b.0 - a.0, b.1 - a.1);
+    let s = (d.0 - c.0, d.1 - c.1);
+    let denominator = r.0 * s.1 - r.1 * s.0;
+    if denominator == 0.0 {
+        return None;
+    }
+    let t = ((c.0 - a.0) * s.1 - (c.1 - a.1) * s.0) / denominator;
+    let u = ((c.0 - a.0) * r.1 - (c.1 - a.1) * r.0) / denominator;
+    if t > 0.0 && t < 1.0 && (0.0..=1.0).contains(&u) {
+        Some(t)
+    } else {
+        None
+    }
+}
+
+/// Even-odd point in polygon test against every ring of the mask
+fn contains(mask: &MultiPolygon<f64>, p: (f64, f64)) -> bool {
+    mask.0.iter().any(|polygon| {
+        let mut inside = false;
+        for ring in std::iter::once(polygon.exterior()).chain(polygon.interiors().iter()) {
+            for w in ring.0.windows(2) {
+                let (a, b) = (w[0], w[1]);
+                if (a.y > p.1) != (b.y > p.1) && p.0 < (b.x - a.x) * (p.1 - a.y) / (b.y - a.y) + a.x {
+                    inside = !inside;
+                }
+            }
+        }
+        inside
+    })
+}
+
+fn to_multi_polygon(values: &[Value]) -> MultiPolygon<f64> {
+    let ring = |r: &Vec<Vec<f64>>| LineString(r.iter().map(|p| Coordinate { x: p[0], y: p[1] }).collect());
+    let polygon = |p: &Vec<Vec<Vec<f64>>>| {
+        let mut rings = p.iter().map(ring);
+        let exterior = rings.next().unwrap_or_else(|| LineString(vec![]));
+        Polygon::new(exterior, rings.collect())
+    };
+    let polygons: Vec<Polygon<f64>> = values.iter().flat_map(|value| match value {
+        Value::Polygon(p) => vec![polygon(p)],
+        Value::MultiPolygon(mp) => mp.iter().map(polygon).collect(),
+        _ => vec![],
+    }).collect();
+    // union the polygons so overlapping coverage does not cancel out
+    polygons.into_iter().fold(MultiPolygon(vec![]), |acc, p| {
+        if acc.0.is_empty() {
+            MultiPolygon(vec![p])
+        } else {
+            acc.union(&p)
+        }
+    })
+}
+
+fn from_polygon(polygon: &Polygon<f64>) -> Vec<Vec<Vec<f64>>> {
+    let ring = |r: &LineString<f64>| r.0.iter().map(|c| vec![c.x, c.y]).collect::<Vec<Vec<f64>>>();
+    std::iter::once(ring(polygon.exterior()))
+        .chain(polygon.interiors().iter().map(ring))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn square(x0: f64, y0: f64, x1: f64, y1: f64) -> Value {
+        Value::Polygon(vec![vec![vec![x0, y0], vec![x1, y0], vec![x1, y1], vec![x0, y1], vec![x0, y0]]])
+    }
+
+    fn cell(name: &str, intended_usage: u8, coverage: Value, features: Vec<Value>) -> Cell {
+        Cell {
+            name: String::from(name),
+            intended_usage: Some(intended_usage),
+            scale: None,
+            coverage: vec![coverage],
+            layers: vec![ChartLayer {
+                name: String::from("DEPARE"),
+                fields: BTreeMap::new(),
+                collection: geojson::FeatureCollection {
+                    bbox: None,
+                    features: features.into_iter().map(|v| Feature {
+                        bbox: None,
+                        geometry: Some(geojson::Geometry::new(v)),
+                        id: None,
+                        properties: None,
+                        foreign_members: None,
+                    }).collect(),
+                    foreign_members: None,
+                },
+            }],
+        }
+    }
+
+    fn zoom_range(feature: &Feature) -> (u64, u64) {
+        let tippecanoe = &feature.foreign_members.as_ref().unwrap()["tippecanoe"];
+        (tippecanoe["minzoom"].as_u64().unwrap(), tippecanoe["maxzoom"].as_u64().unwrap())
+    }
+
+    #[test]
+    fn test_band() {
+        let mut subject = cell("US2", 2, square(0.0, 0.0, 1.0, 1.0), vec![]);
+        assert_eq!(2, subject.band());
+        subject.intended_usage = None;
+        subject.scale = Some(20_000);
+        assert_eq!(5, subject.band());
+    }
+
+    #[test]
+    fn test_composite() {
+        let overview = cell("US2", 2, square(0.0, 0.0, 10.0, 10.0), vec![
+            square(0.0, 0.0, 10.0, 10.0),
+            Value::Point(vec![1.0, 1.0]),
+            Value::Point(vec![8.0, 8.0]),
+        ]);
+        let harbour = cell("US5", 5, square(0.0, 0.0, 5.0, 5.0), vec![square(0.0, 0.0, 5.0, 5.0)]);
+        let result = composite(vec![overview, harbour], 0, 16);
+
+        let overview_features = &result[0][0].collection.features;
+        // the area is whole until the harbour band then clipped to the part outside of the harbour cell
+        assert_eq!(4, overview_features.len());
+        assert_eq!((0, 13), zoom_range(&overview_features[0]));
+        assert_eq!((14, 16), zoom_range(&overview_features[1]));
+        if let Some(Value::Polygon(p)) = overview_features[1].geometry.as_ref().map(|g| &g.value) {
+            assert!(p[0].iter().all(|c| c[0] >= 5.0 || c[1] >= 5.0));
+        } else {
+            panic!("expected a polygon");
+        }
+        // the point inside the harbour coverage is dropped at the harbour band
+        assert_eq!((0, 13), zoom_range(&overview_features[2]));
+        // the point outside of the harbour coverage is shown at every zoom
+        assert_eq!((0, 16), zoom_range(&overview_features[3]));
+
+        let harbour_features = &result[1][0].collection.features;
+        assert_eq!(1, harbour_features.len());
+        assert_eq!((14, 16), zoom_range(&harbour_features[0]));
+    }
+
+    #[test]
+    fn test_set_zoom_range() {
+        let mut sector = Feature {
+            bbox: None,
+            geometry: None,
+            id: None,
+            properties: None,
+            foreign_members: Some(json!({"tippecanoe": {"minzoom": 12, "maxzoom": 12}}).as_object().unwrap().clone()),
+        };
+        assert!(set_zoom_range(&mut sector, 10, 16));
+        assert_eq!((12, 12), zoom_range(&sector));
+        assert!(!set_zoom_range(&mut sector, 14, 16));
+    }
+
+    #[test]
+    fn test_subtract_line() {
+        let mask = to_multi_polygon(&[square(0.0, 0.0, 5.0, 5.0)]);
+        let parts = subtract_line(&[vec![-5.0, 2.0], vec![10.0, 2.0]], &mask);
+        assert_eq!(vec![vec![vec![-5.0, 2.0], vec![0.0, 2.0]], vec![vec![5.0, 2.0], vec![10.0, 2.0]]], parts);
+    }
+}
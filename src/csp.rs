@@ -0,0 +1,622 @@
+//! Conditional symbology procedures (CSP)
+//! https://iho.int/uploads/user/pubs/standards/s-52/S-52%20PresLib%20Ed%204.0.pdf (Part I, 13)
+//!
+//! Procedures are referenced from the look-up tables with `CS(PROCNAME)` and return the drawing
+//! instructions of a feature when they depend on its attributes, neighbouring features or the
+//! mariner's settings. Procedures are matched by name without their version eg `DEPARE01` and
+//! `DEPARE02` both run the current DEPARE03 procedure.
+
+use geojson::Value as GeoValue;
+use serde_json::{Value, json};
+use crate::geojson_builder::JsonObject;
+use crate::{depare, depcnt, lights, soundg};
+
+/// Mariner settings used by the procedures, depths are in meters
+pub struct MarinerSettings {
+    pub shallow_contour: f64,
+    pub safety_contour: f64,
+    pub deep_contour: f64,
+    pub safety_depth: f64,
+    /// two depth shades (shallow and deep) instead of four
+    pub two_shades: bool,
+    /// fill depth areas shallower than the safety contour with the DIAMOND1 pattern
+    pub shallow_pattern: bool,
+}
+
+impl Default for MarinerSettings {
+    fn default() -> Self {
+        MarinerSettings {
+            shallow_contour: 2.0,
+            safety_contour: 3.0,
+            deep_contour: 9.0,
+            safety_depth: 3.0,
+            two_shades: false,
+            shallow_pattern: false,
+        }
+    }
+}
+
+/// Neighbouring features of a cell that procedures depend on
+#[derive(Default)]
+pub struct Context {
+    pub settings: MarinerSettings,
+    /// depth area (DEPARE, DRGARE) polygons and their DRVAL1
+    depth_areas: Vec<(Vec<Vec<Vec<f64>>>, f64)>,
+    /// depth contour values (DEPCNT VALDCO and DEPARE DRVAL1)
+    contours: Vec<f64>,
+    /// positions of floating aids to navigation (buoys, light floats and light vessels)
+    floating: Vec<Vec<f64>>,
+    /// positions and colours of lights
+    lights: Vec<(Vec<f64>, Value)>,
+}
+
+/// Object classes of floating aids to navigation
+pub const FLOATING: [&str; 8] = ["BOYCAR", "BOYINB", "BOYISD", "BOYLAT", "BOYSAW", "BOYSPP", "LITFLT", "LITVES"];
+
+impl Context {
+    pub fn new(settings: MarinerSettings) -> Context {
+        Context { settings, ..Default::default() }
+    }
+
+    /// Records the feature when procedures of other features depend on it
+    pub fn add(&mut self, class: &str, geometry: &GeoValue, properties: &JsonObject) {
+        let number = |key: &str| properties.get(key).and_then(|v| v.as_f64());
+        match (class, geometry) {
+            ("DEPARE", _) | ("DRGARE", _) => {
+                let drval1 = number("DRVAL1");
+                if let (Some(drval1), "DEPARE") = (drval1, class) {
+                    self.contours.push(drval1);
+                }
+                match geometry {
+                    GeoValue::Polygon(p) => self.depth_areas.push((p.clone(), drval1.unwrap_or(-1.0))),
+                    GeoValue::MultiPolygon(mp) => mp.iter().for_each(|p| {
+                        self.depth_areas.push((p.clone(), drval1.unwrap_or(-1.0)))
+                    }),
+                    _ => {}
+                }
+            }
+            ("DEPCNT", _) => {
+                if let Some(valdco) = number("VALDCO") {
+                    self.contours.push(valdco);
+                }
+            }
+            ("LIGHTS", GeoValue::Point(p)) => {
+                self.lights.push((p.clone(), properties.get("COLOUR").cloned().unwrap_or(Value::Null)));
+            }
+            (_, GeoValue::Point(p)) if FLOATING.contains(&class) => self.floating.push(p.clone()),
+            _ => {}
+        }
+    }
+
+    /// DEPVAL02, the least depth of the depth areas at the position
+    pub fn seabed_depth(&self, position: &[f64]) -> Option<f64> {
+        self.depth_areas.iter()
+            .filter(|(polygon, _)| contains(polygon, position))
+            .map(|(_, drval1)| *drval1)
+            .fold(None, |least, d| Some(least.map(|l: f64| l.min(d)).unwrap_or(d)))
+    }
+
+    /// The safety contour displayed, the shallowest contour of the cell at or deeper than the
+    /// mariner's safety contour
+    pub fn safety_contour(&self) -> f64 {
+        self.contours.iter()
+            .filter(|c| **c >= self.settings.safety_contour)
+            .fold(None, |least, c| Some(least.map(|l: f64| l.min(*c)).unwrap_or(*c)))
+            .unwrap_or(self.settings.safety_contour)
+    }
+
+    /// The next shallower contour of the cell
+    pub fn previous_contour(&self, depth: f64) -> Option<f64> {
+        self.contours.iter()
+            .filter(|c| **c < depth)
+            .fold(None, |deepest, c| Some(deepest.map(|d: f64| d.max(*c)).unwrap_or(*c)))
+    }
+
+    pub fn is_floating(&self, position: &[f64]) -> bool {
+        self.floating.iter().any(|p| same_position(p, position))
+    }
+
+    /// Colours of the other lights at the position
+    pub fn other_lights(&self, position: &[f64], colour: Option<&Value>) -> Vec<&Value> {
+        self.lights.iter()
+            .filter(|(p, c)| same_position(p, position) && Some(c) != colour)
+            .map(|(_, c)| c)
+            .collect()
+    }
+}
+
+/// Whether two positions are the same, features that share a position eg a light on a buoy
+/// have the same coordinates
+pub fn same_position(a: &[f64], b: &[f64]) -> bool {
+    a.len() >= 2 && b.len() >= 2 && (a[0] - b[0]).abs() < 1e-7 && (a[1] - b[1]).abs() < 1e-7
+}
+
+/// Even-odd point in polygon test over all of the polygon's rings
+fn contains(polygon: &[Vec<Vec<f64>>], position: &[f64]) -> bool {
+    let (x, y) = (position[0], position[1]);
+    let mut inside = false;
+    for ring in polygon {
+        for (i, a) in ring.iter().enumerate() {
+            let b = &ring[(i + 1) % ring.len()];
+            if (a[1] > y) != (b[1] > y) && x < (b[0] - a[0]) * (y - a[1]) / (b[1] - a[1]) + a[0] {
+                inside = !inside;
+            }
+        }
+    }
+    inside
+}
+
+/// A representative position of the geometry, the first coordinate
+pub fn position(geometry: &GeoValue) -> Option<Vec<f64>> {
+    match geometry {
+        GeoValue::Point(p) => Some(p.clone()),
+        GeoValue::MultiPoint(mp) => mp.first().cloned(),
+        GeoValue::LineString(l) => l.first().cloned(),
+        GeoValue::MultiLineString(ml) => ml.first().and_then(|l| l.first()).cloned(),
+        GeoValue::Polygon(p) => p.first().and_then(|r| r.first()).cloned(),
+        GeoValue::MultiPolygon(mp) => mp.first().and_then(|p| p.first()).and_then(|r| r.first()).cloned(),
+        GeoValue::GeometryCollection(_) => None,
+    }
+}
+
+/// The integer values of a list or single valued attribute eg RESTRN ["7", "14"]
+pub fn list_values(properties: &JsonObject, key: &str) -> Vec<i64> {
+    match properties.get(key) {
+        Some(Value::Array(values)) => values.iter()
+            .filter_map(|v| v.as_i64().or_else(|| v.as_str().and_then(|s| s.parse().ok())))
+            .collect(),
+        Some(value) => value.as_i64().or_else(|| value.as_str().and_then(|s| s.parse().ok()))
+            .into_iter().collect(),
+        None => vec![],
+    }
+}
+
+fn any_of(values: &[i64], of: &[i64]) -> bool {
+    values.iter().any(|v| of.contains(v))
+}
+
+/// QUAPOS 2 - 9 positional accuracy other than surveyed
+pub fn low_accuracy(properties: &JsonObject) -> bool {
+    list_values(properties, "QUAPOS").iter().any(|q| (2..10).contains(q))
+}
+
+/// Runs the procedure, returning its drawing instructions. Procedures may also set properties
+/// on the feature eg the isolated danger flag `ISODGR`.
+pub fn procedure(
+    name: &str,
+    class: &str,
+    geometry: &GeoValue,
+    properties: &mut JsonObject,
+    context: &Context,
+) -> String {
+    match name.get(0..6).unwrap_or(name) {
+        "DEPARE" => depare::depare03(class, properties, &context.settings),
+        "DEPCNT" => depcnt::depcnt03(class, properties, context),
+        "LIGHTS" => lights::lights06(geometry, properties, context),
+        "OBSTRN" => obstrn07(class, geometry, properties, context),
+        "WRECKS" => wrecks05(geometry, properties, context),
+        "SOUNDG" => soundg::soundg03(properties),
+        "RESTRN" => restrn01(properties),
+        "RESARE" => resare02(properties),
+        "TOPMAR" => topmar01(geometry, properties, context),
+        "SLCONS" => slcons04(geometry, properties),
+        "QUAPOS" => quapos01(class, geometry, properties),
+        "DATCVR" => datcvr02(class, properties),
+        // procedures that are not implemented draw nothing
+        _ => String::new(),
+    }
+}
+
+/// DEPVAL02, the depth of an obstruction, wreck or rock from VALSOU with the depth of the
+/// surrounding seabed. Without VALSOU a covered hazard whose expected sounding EXPSOU is within
+/// the range of (1) or deeper than (3) the depth area takes its least depth, otherwise the depth
+/// is assumed from the water level effect WATLEV. Hazards of unknown depth are dangerous.
+fn hazard_depth(geometry: &GeoValue, properties: &JsonObject, context: &Context) -> (f64, Option<f64>) {
+    let seabed = position(geometry).and_then(|p| context.seabed_depth(&p));
+    let watlev = list_values(properties, "WATLEV");
+    let expsou = list_values(properties, "EXPSOU");
+    let depth = properties.get("VALSOU").and_then(|v| v.as_f64()).unwrap_or_else(|| {
+        match seabed {
+            Some(seabed) if watlev.contains(&3) && any_of(&expsou, &[1, 3]) => seabed,
+            _ if list_values(properties, "CATOBS").contains(&6) => 0.01,
+            _ if watlev.contains(&5) => 0.0,
+            _ if watlev.contains(&3) => 0.01,
+            _ => -15.0,
+        }
+    });
+    (depth, seabed)
+}
+
+/// UDWHAZ05, a hazard at or shallower than the safety contour in water deeper than the safety
+/// contour is an isolated danger unless it is always or partly above water (WATLEV 1, 2)
+fn isolated_danger(depth: f64, seabed: Option<f64>, properties: &JsonObject, context: &Context) -> bool {
+    let safety_contour = context.safety_contour();
+    depth <= safety_contour
+        && seabed.map(|s| s >= safety_contour).unwrap_or(false)
+        && !any_of(&list_values(properties, "WATLEV"), &[1, 2])
+}
+
+/// Flags the hazard's least depth VALSOU to be drawn as a sounding (SNDFRM04)
+fn sounding(properties: &mut JsonObject, drawn: bool) {
+    let drawn = drawn && properties.contains_key("VALSOU");
+    properties.insert(String::from("SNDFRM"), json!(drawn));
+}
+
+/// OBSTRN07, obstructions and underwater or awash rocks (UWTROC)
+fn obstrn07(class: &str, geometry: &GeoValue, properties: &mut JsonObject, context: &Context) -> String {
+    let (depth, seabed) = hazard_depth(geometry, properties, context);
+    let valsou = properties.get("VALSOU").and_then(|v| v.as_f64());
+    let watlev = list_values(properties, "WATLEV");
+    let isolated = isolated_danger(depth, seabed, properties, context);
+    properties.insert(String::from("ISODGR"), json!(isolated));
+    match geometry {
+        GeoValue::Point(_) | GeoValue::MultiPoint(_) => {
+            let (symbol, drawn) = if isolated {
+                ("ISODGR01", false)
+            } else if let Some(valsou) = valsou {
+                if valsou > 20.0 {
+                    ("DANGER02", true)
+                } else if class == "UWTROC" {
+                    if any_of(&watlev, &[4, 5]) { ("UWTROC04", false) } else { ("DANGER01", true) }
+                } else if any_of(&watlev, &[1, 2]) {
+                    ("LNDARE01", false)
+                } else if any_of(&watlev, &[4, 5]) {
+                    ("DANGER03", true)
+                } else {
+                    ("DANGER01", true)
+                }
+            } else if class == "UWTROC" {
+                if any_of(&watlev, &[4, 5]) { ("UWTROC04", false) } else { ("UWTROC03", false) }
+            } else if list_values(properties, "CATOBS").contains(&6) {
+                ("OBSTRN01", false)
+            } else if any_of(&watlev, &[1, 2]) {
+                ("OBSTRN11", false)
+            } else if any_of(&watlev, &[4, 5]) {
+                ("OBSTRN03", false)
+            } else {
+                ("OBSTRN01", false)
+            };
+            sounding(properties, drawn);
+            format!("SY({})", symbol)
+        }
+        GeoValue::LineString(_) | GeoValue::MultiLineString(_) => {
+            if isolated || valsou.is_some() {
+                String::from("LS(DOTT,2,CHBLK)")
+            } else {
+                String::from("LS(DASH,2,CHBLK)")
+            }
+        }
+        _ => {
+            if isolated {
+                String::from("AC(DEPVS);AP(FOULAR01);LS(DOTT,2,CHBLK);SY(ISODGR01)")
+            } else if any_of(&watlev, &[1, 2]) {
+                String::from("AC(CHBRN);LS(SOLD,2,CSTLN)")
+            } else if watlev.contains(&4) {
+                String::from("AC(DEPIT);LS(DASH,2,CSTLN)")
+            } else if list_values(properties, "CATOBS").contains(&6) {
+                String::from("AP(FOULAR01);LS(DOTT,2,CHBLK)")
+            } else {
+                String::from("AC(DEPVS);LS(DOTT,2,CHBLK)")
+            }
+        }
+    }
+}
+
+/// WRECKS05, wrecks
+fn wrecks05(geometry: &GeoValue, properties: &mut JsonObject, context: &Context) -> String {
+    let (depth, seabed) = hazard_depth(geometry, properties, context);
+    let valsou = properties.get("VALSOU").and_then(|v| v.as_f64());
+    let watlev = list_values(properties, "WATLEV");
+    let catwrk = list_values(properties, "CATWRK");
+    let isolated = isolated_danger(depth, seabed, properties, context);
+    properties.insert(String::from("ISODGR"), json!(isolated));
+    match geometry {
+        GeoValue::Point(_) | GeoValue::MultiPoint(_) => {
+            let (symbol, drawn) = if isolated {
+                ("ISODGR01", false)
+            } else if let Some(valsou) = valsou {
+                if valsou <= 20.0 { ("DANGER01", true) } else { ("DANGER02", true) }
+            } else if any_of(&catwrk, &[4, 5]) || any_of(&watlev, &[1, 2, 4, 5]) {
+                ("WRECKS01", false)
+            } else if catwrk.contains(&1) && watlev.contains(&3) {
+                ("WRECKS04", false)
+            } else {
+                ("WRECKS05", false)
+            };
+            sounding(properties, drawn);
+            format!("SY({})", symbol)
+        }
+        _ => {
+            if isolated {
+                String::from("AC(DEPVS);LS(DOTT,2,CHBLK);SY(ISODGR01)")
+            } else if any_of(&watlev, &[1, 2]) {
+                String::from("AC(CHBRN);LS(SOLD,2,CSTLN)")
+            } else if watlev.contains(&4) {
+                String::from("AC(DEPIT);LS(DASH,2,CSTLN)")
+            } else {
+                String::from("AC(DEPVS);LS(DOTT,2,CSTLN)")
+            }
+        }
+    }
+}
+
+/// RESTRN01 / RESCSP02, the symbol of the most significant restriction
+///     1, 2 anchoring prohibited / restricted
+///     3 - 6, 24 fishing and trawling prohibited / restricted
+///     7, 8, 14 entry prohibited / restricted
+///     13, 16, 17, 23, 25 - 27 other restrictions
+///     9 - 12, 15, 18 - 22 other information
+pub fn restrn01(properties: &JsonObject) -> String {
+    let restrn = list_values(properties, "RESTRN");
+    if restrn.is_empty() {
+        return String::new();
+    }
+    let other_restriction = any_of(&restrn, &[13, 16, 17, 23, 25, 26, 27]);
+    let other_information = any_of(&restrn, &[9, 10, 11, 12, 15, 18, 19, 20, 21, 22]);
+    let variant = |restriction: bool, name: &str| {
+        if restriction {
+            format!("SY({}61)", name)
+        } else if other_information {
+            format!("SY({}71)", name)
+        } else {
+            format!("SY({}51)", name)
+        }
+    };
+    if any_of(&restrn, &[7, 8, 14]) {
+        variant(any_of(&restrn, &[1, 2, 3, 4, 5, 6]) || other_restriction, "ENTRES")
+    } else if any_of(&restrn, &[1, 2]) {
+        variant(any_of(&restrn, &[3, 4, 5, 6]) || other_restriction, "ACHRES")
+    } else if any_of(&restrn, &[3, 4, 5, 6, 24]) {
+        variant(other_restriction, "FSHRES")
+    } else if other_restriction {
+        variant(false, "CTYARE")
+    } else if other_information {
+        String::from("SY(INFARE51)")
+    } else {
+        String::from("SY(RSRDEF51)")
+    }
+}
+
+/// RESARE02, the restriction symbol of a restricted area and its complex boundary line. Areas
+/// without restrictions are symbolized from their category CATREA
+///     1, 8, 9, 12, 14, 18, 19, 21, 24, 25, 26 caution areas eg offshore safety zones
+///     4, 5, 6, 7, 10, 20, 22, 23 information areas eg nature reserves
+pub fn resare02(properties: &JsonObject) -> String {
+    let restrn = list_values(properties, "RESTRN");
+    if !restrn.is_empty() {
+        let line = if any_of(&restrn, &[7, 8, 14]) {
+            "ENTRES51"
+        } else if any_of(&restrn, &[1, 2]) {
+            "ACHRES51"
+        } else if any_of(&restrn, &[3, 4, 5, 6, 24]) {
+            "FSHRES51"
+        } else if any_of(&restrn, &[13, 16, 17, 23, 25, 26, 27]) {
+            "CTYARE51"
+        } else {
+            "RESARE51"
+        };
+        return format!("{};LC({})", restrn01(properties), line);
+    }
+    let catrea = list_values(properties, "CATREA");
+    if any_of(&catrea, &[1, 8, 9, 12, 14, 18, 19, 21, 24, 25, 26]) {
+        String::from("SY(CTYARE51);LC(CTYARE51)")
+    } else if any_of(&catrea, &[4, 5, 6, 7, 10, 20, 22, 23]) {
+        String::from("SY(INFARE51);LC(CTYARE51)")
+    } else {
+        String::from("SY(RSRDEF51);LC(CTYARE51)")
+    }
+}
+
+/// TOPMAR01, topmarks on floating (buoys) and rigid (beacons) aids to navigation differ
+fn topmar01(geometry: &GeoValue, properties: &JsonObject, context: &Context) -> String {
+    let topshp = match list_values(properties, "TOPSHP").first() {
+        Some(topshp) => *topshp,
+        None => return String::from("SY(QUESMRK1)"),
+    };
+    let floating = position(geometry).map(|p| context.is_floating(&p)).unwrap_or(false);
+    format!("SY({})", topmark_symbol(topshp, floating))
+}
+
+/// The topmark symbol of the topmark shape TOPSHP, topmarks of buoys lean with the buoy symbol
+pub fn topmark_symbol(topshp: i64, floating: bool) -> &'static str {
+    if floating {
+        match topshp {
+            1 | 24 | 29 => "TOPMAR02",
+            2 | 25 => "TOPMAR04",
+            3 | 18 | 26 | 32 => "TOPMAR10",
+            4 => "TOPMAR12",
+            5 | 19 | 21 => "TOPMAR13",
+            6 | 12 | 20 | 22 | 23 | 31 => "TOPMAR14",
+            7 => "TOPMAR65",
+            8 | 27 | 30 => "TOPMAR17",
+            9 => "TOPMAR16",
+            10 => "TOPMAR08",
+            11 => "TOPMAR07",
+            13 => "TOPMAR05",
+            14 => "TOPMAR06",
+            28 => "TOPMAR18",
+            _ => "TMARDEF2",
+        }
+    } else {
+        match topshp {
+            1 | 24 | 29 => "TOPMAR22",
+            2 | 25 => "TOPMAR24",
+            3 | 18 | 26 | 32 => "TOPMAR30",
+            4 => "TOPMAR32",
+            5 | 19 | 21 => "TOPMAR33",
+            6 | 20 | 22 | 23 => "TOPMAR34",
+            7 => "TOPMAR85",
+            8 | 27 | 30 => "TOPMAR86",
+            9 => "TOPMAR36",
+            10 => "TOPMAR28",
+            11 => "TOPMAR27",
+            12 | 31 => "TOPMAR14",
+            13 => "TOPMAR25",
+            14 => "TOPMAR26",
+            15 => "TOPMAR88",
+            16 => "TOPMAR87",
+            28 => "TOPMAR89",
+            _ => "TMARDEF1",
+        }
+    }
+}
+
+/// SLCONS04, shoreline constructions
+fn slcons04(geometry: &GeoValue, properties: &JsonObject) -> String {
+    if let GeoValue::Point(_) | GeoValue::MultiPoint(_) = geometry {
+        return if low_accuracy(properties) { String::from("SY(LOWACC01)") } else { String::new() };
+    }
+    if low_accuracy(properties) {
+        String::from("LC(LOWACC01)")
+    } else if any_of(&list_values(properties, "CONDTN"), &[1, 2]) {
+        String::from("LS(DASH,1,CSTLN)")
+    } else if any_of(&list_values(properties, "CATSLC"), &[6, 15, 16]) {
+        String::from("LS(SOLD,4,CSTLN)")
+    } else if any_of(&list_values(properties, "WATLEV"), &[3, 4]) {
+        String::from("LS(DASH,2,CSTLN)")
+    } else {
+        String::from("LS(SOLD,2,CSTLN)")
+    }
+}
+
+/// QUAPOS01, coastlines and land area edges with their positional accuracy
+fn quapos01(class: &str, geometry: &GeoValue, properties: &JsonObject) -> String {
+    match geometry {
+        GeoValue::Point(_) | GeoValue::MultiPoint(_) => {
+            if low_accuracy(properties) { String::from("SY(LOWACC01)") } else { String::new() }
+        }
+        _ => {
+            if low_accuracy(properties) {
+                String::from("LC(LOWACC21)")
+            } else if class == "COALNE" && list_values(properties, "CONRAD").contains(&1) {
+                String::from("LS(SOLD,3,CHMGF);LS(SOLD,1,CSTLN)")
+            } else {
+                String::from("LS(SOLD,1,CSTLN)")
+            }
+        }
+    }
+}
+
+/// DATCVR02, the limit of hydrographic office data (M_COVR CATCOV 1)
+fn datcvr02(class: &str, properties: &JsonObject) -> String {
+    if class == "M_COVR" && list_values(properties, "CATCOV").contains(&1) {
+        String::from("LC(HODATA01)")
+    } else {
+        String::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn properties(value: Value) -> JsonObject {
+        value.as_object().unwrap().clone()
+    }
+
+    fn square(size: f64) -> GeoValue {
+        GeoValue::Polygon(vec![vec![vec![0.0, 0.0], vec![size, 0.0], vec![size, size], vec![0.0, size], vec![0.0, 0.0]]])
+    }
+
+    #[test]
+    fn test_context() {
+        let mut context = Context::default();
+        context.add("DEPARE", &square(10.0), &properties(json!({"DRVAL1": 5.4, "DRVAL2": 9.1})));
+        context.add("DEPARE", &square(2.0), &properties(json!({"DRVAL1": 1.8, "DRVAL2": 3.6})));
+        context.add("DEPCNT", &GeoValue::LineString(vec![]), &properties(json!({"VALDCO": 3.6})));
+        assert_eq!(Some(1.8), context.seabed_depth(&[1.0, 1.0]));
+        assert_eq!(Some(5.4), context.seabed_depth(&[5.0, 5.0]));
+        assert_eq!(None, context.seabed_depth(&[11.0, 5.0]));
+        assert_eq!(3.6, context.safety_contour());
+        assert_eq!(Some(1.8), context.previous_contour(3.6));
+        assert_eq!(None, context.previous_contour(1.8));
+    }
+
+    #[test]
+    fn test_obstrn07() {
+        let mut context = Context::default();
+        context.add("DEPARE", &square(10.0), &properties(json!({"DRVAL1": 5.4, "DRVAL2": 9.1})));
+        context.add("DEPCNT", &GeoValue::LineString(vec![]), &properties(json!({"VALDCO": 3.6})));
+        let point = GeoValue::Point(vec![5.0, 5.0]);
+        let mut obstruction = properties(json!({"VALSOU": 1.2}));
+        assert_eq!("SY(ISODGR01)", procedure("OBSTRN04", "OBSTRN", &point, &mut obstruction, &context));
+        assert_eq!(json!(true), obstruction["ISODGR"]);
+        let mut deep = properties(json!({"VALSOU": 14.6}));
+        assert_eq!("SY(DANGER01)", procedure("OBSTRN04", "OBSTRN", &point, &mut deep, &context));
+        assert_eq!(json!(true), deep["SNDFRM"]);
+        let mut covers = properties(json!({"VALSOU": 14.6, "WATLEV": 5}));
+        assert_eq!("SY(DANGER03)", procedure("OBSTRN04", "OBSTRN", &point, &mut covers, &context));
+        let mut rock = properties(json!({"WATLEV": 4}));
+        assert_eq!("SY(UWTROC04)", procedure("OBSTRN04", "UWTROC", &GeoValue::Point(vec![20.0, 20.0]), &mut rock, &context));
+        assert_eq!(json!(false), rock["SNDFRM"]);
+        let mut sounded_rock = properties(json!({"WATLEV": 3, "VALSOU": 4.2}));
+        assert_eq!("SY(DANGER01)", procedure("OBSTRN04", "UWTROC", &GeoValue::Point(vec![20.0, 20.0]), &mut sounded_rock, &context));
+        // an obstruction of unknown depth in deep water is dangerous unless it is expected to be
+        // within the range of the depth area
+        let mut unknown = properties(json!({}));
+        assert_eq!("SY(ISODGR01)", procedure("OBSTRN04", "OBSTRN", &point, &mut unknown, &context));
+        let mut expected = properties(json!({"WATLEV": 3, "EXPSOU": 1}));
+        assert_eq!("SY(OBSTRN01)", procedure("OBSTRN04", "OBSTRN", &point, &mut expected, &context));
+        assert_eq!(json!(false), expected["ISODGR"]);
+        // a dry obstruction is not an isolated danger
+        let mut dries = properties(json!({"VALSOU": 1.2, "WATLEV": 1}));
+        assert_eq!("SY(LNDARE01)", procedure("OBSTRN04", "OBSTRN", &point, &mut dries, &context));
+    }
+
+    #[test]
+    fn test_wrecks05() {
+        let context = Context::default();
+        let point = GeoValue::Point(vec![5.0, 5.0]);
+        let mut showing = properties(json!({"CATWRK": 5, "WATLEV": 2}));
+        assert_eq!("SY(WRECKS01)", procedure("WRECKS02", "WRECKS", &point, &mut showing, &context));
+        let mut dangerous = properties(json!({"CATWRK": 2, "WATLEV": 3}));
+        assert_eq!("SY(WRECKS05)", procedure("WRECKS02", "WRECKS", &point, &mut dangerous, &context));
+        let mut sounded = properties(json!({"CATWRK": 2, "VALSOU": 24.0}));
+        assert_eq!("SY(DANGER02)", procedure("WRECKS02", "WRECKS", &point, &mut sounded, &context));
+        assert_eq!(json!(true), sounded["SNDFRM"]);
+        let mut area = properties(json!({"WATLEV": 3}));
+        assert_eq!("AC(DEPVS);LS(DOTT,2,CSTLN)", procedure("WRECKS02", "WRECKS", &square(1.0), &mut area, &context));
+    }
+
+    #[test]
+    fn test_restrn01() {
+        assert_eq!("SY(ENTRES61)", restrn01(&properties(json!({"RESTRN": ["7", "1"]}))));
+        assert_eq!("SY(ACHRES51)", restrn01(&properties(json!({"RESTRN": ["2"]}))));
+        assert_eq!("SY(FSHRES71)", restrn01(&properties(json!({"RESTRN": ["3", "9"]}))));
+        assert_eq!("SY(INFARE51)", restrn01(&properties(json!({"RESTRN": ["15"]}))));
+        assert_eq!("SY(ENTRES61);LC(ENTRES51)", resare02(&properties(json!({"RESTRN": ["7", "1"]}))));
+        assert_eq!("SY(FSHRES71);LC(FSHRES51)", resare02(&properties(json!({"RESTRN": ["3", "9"]}))));
+        assert_eq!("SY(INFARE51);LC(RESARE51)", resare02(&properties(json!({"RESTRN": ["15"]}))));
+        assert_eq!("SY(CTYARE51);LC(CTYARE51)", resare02(&properties(json!({"CATREA": ["1"]}))));
+        assert_eq!("SY(RSRDEF51);LC(CTYARE51)", resare02(&properties(json!({}))));
+        assert_eq!("", restrn01(&properties(json!({}))));
+    }
+
+    #[test]
+    fn test_topmar01() {
+        let mut context = Context::default();
+        let point = GeoValue::Point(vec![1.0, 1.0]);
+        let topmark = properties(json!({"TOPSHP": 2}));
+        assert_eq!("SY(TOPMAR24)", topmar01(&point, &topmark, &context));
+        context.add("BOYLAT", &point, &properties(json!({})));
+        assert_eq!("SY(TOPMAR04)", topmar01(&point, &topmark, &context));
+        assert_eq!("SY(QUESMRK1)", topmar01(&point, &properties(json!({})), &context));
+    }
+
+    #[test]
+    fn test_slcons04_quapos01() {
+        let line = GeoValue::LineString(vec![vec![0.0, 0.0], vec![1.0, 1.0]]);
+        assert_eq!("LS(SOLD,2,CSTLN)", slcons04(&line, &properties(json!({"CATSLC": 4}))));
+        assert_eq!("LS(SOLD,4,CSTLN)", slcons04(&line, &properties(json!({"CATSLC": 6}))));
+        assert_eq!("LS(DASH,1,CSTLN)", slcons04(&line, &properties(json!({"CONDTN": 2}))));
+        assert_eq!("LC(LOWACC01)", slcons04(&line, &properties(json!({"QUAPOS": 4}))));
+        assert_eq!("LS(SOLD,1,CSTLN)", quapos01("LNDARE", &line, &properties(json!({}))));
+        assert_eq!("LC(LOWACC21)", quapos01("COALNE", &line, &properties(json!({"QUAPOS": 5}))));
+    }
+
+    #[test]
+    fn test_datcvr02() {
+        assert_eq!("LC(HODATA01)", datcvr02("M_COVR", &properties(json!({"CATCOV": 1}))));
+        assert_eq!("", datcvr02("M_COVR", &properties(json!({"CATCOV": 2}))));
+    }
+}
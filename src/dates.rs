@@ -0,0 +1,177 @@
+use serde_json::json;
+use crate::geojson_builder::JsonObject;
+
+/// Dates of the period in which a feature exists, fixed dates CCYYMMDD
+const FIXED: [&str; 2] = ["DATSTA", "DATEND"];
+/// Dates of the periodic range in which a feature exists each year eg a seasonal buoy, fixed dates
+/// or periodic dates --MMDD
+const PERIODIC: [&str; 2] = ["PERSTA", "PEREND"];
+
+/// DATSTA, DATEND, PERSTA and PEREND
+///
+/// S-57 encodes dates as CCYYMMDD, truncated dates as CCYYMM or CCYY and periodic dates that
+/// recur every year as --MMDD or --MM (S-57 Part 3, 7.7.1.4). Dates are written as ISO 8601
+/// dates, CCYY-MM-DD, CCYY-MM and CCYY, and recurring dates as --MM-DD and --MM. Truncated dates
+/// do not compare as strings with a full date, 2021-04 sorts before 2021-04-15, so a style or
+/// client has to pad them to the first or last day as `in_effect` does. Values that are not
+/// dates are removed.
+pub fn normalize(properties: &mut JsonObject) {
+    for attribute in FIXED.iter().chain(PERIODIC.iter()) {
+        let value = match properties.get(*attribute).and_then(|v| v.as_str()) {
+            Some(value) => value.trim().to_string(),
+            None => continue,
+        };
+        match iso_date(&value) {
+            Some(date) => properties.insert(attribute.to_string(), json!(date)),
+            None => properties.remove(*attribute),
+        };
+    }
+}
+
+/// An S-57 or ISO date as an ISO 8601 date
+fn iso_date(value: &str) -> Option<String> {
+    let (periodic, digits) = match value.strip_prefix("--") {
+        Some(rest) => (true, rest.replace('-', "")),
+        None => (false, value.replace('-', "")),
+    };
+    if !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let parts: Vec<&str> = match (periodic, digits.len()) {
+        (true, 2) => vec![&digits[0..2]],
+        (true, 4) => vec![&digits[0..2], &digits[2..4]],
+        (false, 4) => return Some(digits).filter(|year| year != "0000"),
+        (false, 6) => vec![&digits[0..4], &digits[4..6]],
+        (false, 8) => vec![&digits[0..4], &digits[4..6], &digits[6..8]],
+        _ => return None,
+    };
+    let (year, month, day) = if periodic {
+        (None, parts[0], parts.get(1))
+    } else {
+        (Some(parts[0]), parts[1], parts.get(2))
+    };
+    let year: Option<u32> = match year {
+        Some(year) => Some(year.parse().ok().filter(|y| *y > 0)?),
+        None => None,
+    };
+    let month: u32 = month.parse().ok()?;
+    let day: u32 = day.map(|d| d.parse().ok()).unwrap_or(Some(1))?;
+    if !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month) {
+        return None;
+    }
+    let date = parts.join("-");
+    Some(if periodic { format!("--{}", date) } else { date })
+}
+
+/// The number of days of the month, February of a periodic date without a year has 29 days
+fn days_in_month(year: Option<u32>, month: u32) -> u32 {
+    match month {
+        4 | 6 | 9 | 11 => 30,
+        2 => match year {
+            Some(year) if !(year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)) => 28,
+            _ => 29,
+        },
+        _ => 31,
+    }
+}
+
+/// Parses a date given as CCYY-MM-DD or CCYYMMDD to the number CCYYMMDD
+pub fn parse_date(value: &str) -> Option<u32> {
+    match iso_date(value.trim()) {
+        Some(date) if date.len() == 10 => date.replace('-', "").parse().ok(),
+        _ => None,
+    }
+}
+
+/// Whether the feature exists on the date CCYYMMDD. The feature exists between its start DATSTA
+/// and end DATEND and, when it is periodic, between PERSTA and PEREND of the year (a periodic
+/// range may wrap the new year eg a winter buoy from --11-15 to --04-01). Both bounds are padded
+/// before they are compared, truncated dates span the whole month or year and a missing bound is
+/// open. A period with a periodic and a fixed bound recurs between the month and day of its
+/// bounds every year until, or from, the fixed date.
+pub fn in_effect(properties: &JsonObject, date: u32) -> bool {
+    let period_start = bound(properties, "PERSTA", false);
+    let period_end = bound(properties, "PEREND", true);
+    let in_period = if period_start.iter().chain(period_end.iter()).any(|(periodic, _)| *periodic) {
+        let day = date % 10000;
+        let start = period_start.map(|(_, s)| s % 10000).unwrap_or(101);
+        let end = period_end.map(|(_, e)| e % 10000).unwrap_or(1231);
+        let recurring = if start <= end { start <= day && day <= end } else { start <= day || day <= end };
+        let fixed_bound = |bound: Option<(bool, u32)>| bound.filter(|(periodic, _)| !periodic);
+        recurring && within(fixed_bound(period_start), fixed_bound(period_end), date)
+    } else {
+        within(period_start, period_end, date)
+    };
+    in_period && within(bound(properties, "DATSTA", false), bound(properties, "DATEND", true), date)
+}
+
+/// A bound of a period, whether it is periodic and its date CCYYMMDD or MMDD padded to the first
+/// day of a start or the last day of an end
+fn bound(properties: &JsonObject, attribute: &str, end: bool) -> Option<(bool, u32)> {
+    let value = iso_date(properties.get(attribute)?.as_str()?.trim())?;
+    let periodic = value.starts_with("--");
+    let digits = value.replace('-', "");
+    let padded = match (periodic, digits.len(), end) {
+        (true, 2, false) | (false, 6, false) => format!("{}01", digits),
+        (false, 4, false) => format!("{}0101", digits),
+        (true, 2, true) | (false, 6, true) => format!("{}31", digits),
+        (false, 4, true) => format!("{}1231", digits),
+        _ => digits,
+    };
+    padded.parse().ok().map(|d| (periodic, d))
+}
+
+fn within(start: Option<(bool, u32)>, end: Option<(bool, u32)>, date: u32) -> bool {
+    start.map(|(_, s)| s <= date).unwrap_or(true) && end.map(|(_, e)| date <= e).unwrap_or(true)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn dates(value: serde_json::Value) -> JsonObject {
+        let mut properties = value.as_object().unwrap().clone();
+        normalize(&mut properties);
+        properties
+    }
+
+    #[test]
+    fn test_normalize() {
+        let properties = dates(json!({"DATSTA": "20210401", "DATEND": "2022", "PERSTA": "--1115", "PEREND": "unknown"}));
+        assert_eq!(json!("2021-04-01"), properties["DATSTA"]);
+        assert_eq!(json!("2022"), properties["DATEND"]);
+        assert_eq!(json!("--11-15"), properties["PERSTA"]);
+        assert!(properties.get("PEREND").is_none());
+        assert_eq!(Some(20210401), parse_date("2021-04-01"));
+        assert_eq!(None, parse_date("2021-13-01"));
+        assert_eq!(None, parse_date("20240231"));
+        assert_eq!(Some(20240229), parse_date("20240229"));
+        assert_eq!(None, parse_date("20230229"));
+        assert_eq!(None, parse_date("00000101"));
+        let leap = dates(json!({"PERSTA": "--0229", "DATEND": "0000"}));
+        assert_eq!(json!("--02-29"), leap["PERSTA"]);
+        assert!(leap.get("DATEND").is_none());
+    }
+
+    #[test]
+    fn test_in_effect() {
+        let seasonal = dates(json!({"PERSTA": "--1115", "PEREND": "--0401"}));
+        assert!(in_effect(&seasonal, 20210101));
+        assert!(!in_effect(&seasonal, 20210701));
+        let temporary = dates(json!({"DATSTA": "202104", "DATEND": "2021"}));
+        assert!(!in_effect(&temporary, 20210331));
+        assert!(in_effect(&temporary, 20210401));
+        assert!(in_effect(&temporary, 20211231));
+        assert!(!in_effect(&temporary, 20220101));
+        assert!(in_effect(&dates(json!({})), 20220101));
+        // a seasonal buoy that is withdrawn after the season of 2022
+        let withdrawn = dates(json!({"PERSTA": "--1115", "PEREND": "20220401"}));
+        assert!(in_effect(&withdrawn, 20211201));
+        assert!(in_effect(&withdrawn, 20220301));
+        assert!(!in_effect(&withdrawn, 20210701));
+        assert!(!in_effect(&withdrawn, 20221201));
+        let april = dates(json!({"PERSTA": "202104", "PEREND": "202104"}));
+        assert!(in_effect(&april, 20210415));
+        assert!(!in_effect(&april, 20210501));
+    }
+}
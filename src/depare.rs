@@ -1,5 +1,6 @@
-use serde_json::json;
-use serde_json::Value;
+use serde_json::{Value, json};
+use crate::geojson_builder::JsonObject;
+use crate::csp::MarinerSettings;
 
 /// DEPARE, Depth Area
 /// Geometric primitives: L,A
@@ -20,48 +21,108 @@ use serde_json::Value;
 ///     depth contour; dredged area; sounding; obstruction; sea area/named water area; unsurveyed area; wreck;
 ///
 /// The geometric primitive line is removed for the object class, S-57 Supplement No. 3 (Edition 3.1.3), 3.3
-pub fn layers(colors: &Value) -> Vec<Value> {
-    vec![
-        json!({
-            "id": "DEPARE_fill_2",
-            "type": "fill",
-            "source": "src_senc",
-            "source-layer": "DEPARE",
-            "filter": ["all", ["==", "$type", "Polygon"], ["<=", "DRVAL1", 9.0]],
-            "paint": {
-                "fill-color": colors["DEPMD"]
-            }
-        }),
-        json!({
-            "id": "DEPARE_fill_1",
-            "type": "fill",
-            "source": "src_senc",
-            "source-layer": "DEPARE",
-            "filter": ["all", ["==", "$type", "Polygon"], ["<=", "DRVAL1", 3.0]],
-            "paint": {
-                "fill-color": colors["DEPVS"]
-            }
-        }),
-        json!({
-            "id": "DEPARE_fill_0",
-            "type": "fill",
-            "source": "src_senc",
-            "source-layer": "DEPARE",
-            "filter": ["all", ["==", "$type", "Polygon"], ["<", "DRVAL1", 0.0], ["<=", "DRVAL2", 0.0]],
-            "paint": {
-                "fill-color": colors["DEPIT"]
-            }
-        }),
-        json!({
-            "id": "DEPARE_line",
-            "type": "line",
-            "source": "src_senc",
-            "source-layer": "DEPARE",
-            "filter": ["any", ["==", "$type", "Polygon"], ["==", "$type", "LineString"]],
-            "paint": {
-                "line-color": colors["CSTLN"],
-                "line-width": 0.5
-            }
-        })
-    ]
+///
+/// Conditional symbology procedure DEPARE03, the area colour of depth areas and dredged areas
+pub fn depare03(class: &str, properties: &JsonObject, settings: &MarinerSettings) -> String {
+    let drval1 = properties.get("DRVAL1").and_then(|v| v.as_f64()).unwrap_or(-1.0);
+    let drval2 = properties.get("DRVAL2").and_then(|v| v.as_f64()).unwrap_or(drval1 + 0.01);
+    let mut instruction = format!("AC({})", seabed01(drval1, drval2, settings));
+    if settings.shallow_pattern && drval1 < settings.safety_contour {
+        instruction.push_str(";AP(DIAMOND1)");
+    }
+    if class == "DRGARE" {
+        instruction.push_str(";AP(DRGARE01);LS(DASH,1,CHGRF)");
+    }
+    instruction
+}
+
+/// SEABED01, the depth shade of a depth range
+///     DEPIT intertidal
+///     DEPVS very shallow, shallower than the shallow contour
+///     DEPMS medium shallow, shallower than the safety contour
+///     DEPMD medium deep, shallower than the deep contour
+///     DEPDW deep water
+/// With two shades only DEPVS (shallower than the safety contour) and DEPDW are used.
+pub fn seabed01(drval1: f64, drval2: f64, settings: &MarinerSettings) -> &'static str {
+    let mut colour = "DEPIT";
+    if drval1 >= 0.0 && drval2 > 0.0 {
+        colour = "DEPVS";
+    }
+    if settings.two_shades {
+        if drval1 >= settings.safety_contour && drval2 > settings.safety_contour {
+            colour = "DEPDW";
+        }
+    } else {
+        if drval1 >= settings.shallow_contour && drval2 > settings.shallow_contour {
+            colour = "DEPMS";
+        }
+        if drval1 >= settings.safety_contour && drval2 > settings.safety_contour {
+            colour = "DEPMD";
+        }
+        if drval1 >= settings.deep_contour && drval2 > settings.deep_contour {
+            colour = "DEPDW";
+        }
+    }
+    colour
+}
+
+/// The SEABED01 depth shade as a style expression so styles can be generated for the mariner's
+/// settings without rendering the tiles again
+pub fn fill_colour(colors: &Value, settings: &MarinerSettings) -> Value {
+    let drval1 = json!(["coalesce", ["get", "DRVAL1"], -1]);
+    let drval2 = json!(["coalesce", ["get", "DRVAL2"], ["+", drval1, 0.01]]);
+    let deeper = |depth: f64| json!(["all", [">=", drval1, depth], [">", drval2, depth]]);
+    let mut expression = vec![json!("case")];
+    if settings.two_shades {
+        expression.extend(vec![deeper(settings.safety_contour), colors["DEPDW"].clone()]);
+    } else {
+        expression.extend(vec![
+            deeper(settings.deep_contour), colors["DEPDW"].clone(),
+            deeper(settings.safety_contour), colors["DEPMD"].clone(),
+            deeper(settings.shallow_contour), colors["DEPMS"].clone(),
+        ]);
+    }
+    expression.extend(vec![deeper(0.0), colors["DEPVS"].clone(), colors["DEPIT"].clone()]);
+    Value::Array(expression)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn depare(drval1: f64, drval2: f64) -> JsonObject {
+        json!({"DRVAL1": drval1, "DRVAL2": drval2}).as_object().unwrap().clone()
+    }
+
+    #[test]
+    fn test_depare03() {
+        // depth ranges of US5WA22M
+        let settings = MarinerSettings::default();
+        assert_eq!("AC(DEPIT)", depare03("DEPARE", &depare(-3.3, 0.0), &settings));
+        assert_eq!("AC(DEPVS)", depare03("DEPARE", &depare(0.0, 1.8), &settings));
+        assert_eq!("AC(DEPVS)", depare03("DEPARE", &depare(1.8, 3.6), &settings));
+        assert_eq!("AC(DEPMD)", depare03("DEPARE", &depare(3.6, 5.4), &settings));
+        assert_eq!("AC(DEPMD)", depare03("DEPARE", &depare(5.4, 9.1), &settings));
+        assert_eq!("AC(DEPDW)", depare03("DEPARE", &depare(9.1, 18.2), &settings));
+        assert_eq!("AC(DEPMD);AP(DRGARE01);LS(DASH,1,CHGRF)", depare03("DRGARE", &depare(5.4, 9.1), &settings));
+
+        let shallow = MarinerSettings { shallow_contour: 1.8, ..Default::default() };
+        assert_eq!("AC(DEPMS)", depare03("DEPARE", &depare(1.8, 3.6), &shallow));
+
+        let two_shades = MarinerSettings { two_shades: true, shallow_pattern: true, ..Default::default() };
+        assert_eq!("AC(DEPVS);AP(DIAMOND1)", depare03("DEPARE", &depare(1.8, 3.6), &two_shades));
+        assert_eq!("AC(DEPDW)", depare03("DEPARE", &depare(3.6, 5.4), &two_shades));
+    }
+
+    #[test]
+    fn test_fill_colour() {
+        let colors = crate::colors::COLORS["DAY_BRIGHT"].clone();
+        let four_shades = fill_colour(&colors, &MarinerSettings::default());
+        assert_eq!(10, four_shades.as_array().unwrap().len());
+        assert_eq!(json!([">=", ["coalesce", ["get", "DRVAL1"], -1], 9.0]), four_shades[1][1]);
+        let two_shades = fill_colour(&colors, &MarinerSettings { two_shades: true, ..Default::default() });
+        assert_eq!(6, two_shades.as_array().unwrap().len());
+        assert_eq!(colors["DEPDW"], two_shades[2]);
+        assert_eq!(colors["DEPIT"], two_shades[5]);
+    }
 }
@@ -1,5 +1,10 @@
-use serde_json::json;
-use serde_json::Value;
+use serde_json::{Value, json};
+use crate::geojson_builder::JsonObject;
+use crate::csp::{self, Context, MarinerSettings};
+use crate::units;
+
+/// Zoom from which contours other than the safety contour are labelled
+const LABEL_MINZOOM: f64 = 12.0;
 
 /// DEPCNT, Depth Contour
 /// Geometric primitives: LineString
@@ -17,18 +22,154 @@ use serde_json::Value;
 ///     Drying contours are encoded with negative values.
 /// Distinction:
 ///     sounding; depth area; coastline;
-pub fn layers(colors: &Value) -> Vec<Value> {
+///
+/// Conditional symbology procedure DEPCNT03, depth contours and depth area edges. The safety
+/// contour is drawn wider and flagged with `SCNT`. The next shallower contour of the cell is kept
+/// in `CNTPRV` so styles can select the safety contour of other mariner settings.
+pub fn depcnt03(class: &str, properties: &mut JsonObject, context: &Context) -> String {
+    let depth = match class {
+        "DEPARE" => properties.get("DRVAL1"),
+        _ => properties.get("VALDCO"),
+    }.and_then(|v| v.as_f64()).unwrap_or(0.0);
+    if let Some(previous) = context.previous_contour(depth) {
+        properties.insert(String::from("CNTPRV"), json!(previous));
+    }
+    let low_accuracy = csp::low_accuracy(properties);
+    if (depth - context.safety_contour()).abs() < 1e-6 {
+        properties.insert(String::from("SCNT"), Value::Bool(true));
+        if low_accuracy { "LS(DASH,2,DEPSC)" } else { "LS(SOLD,2,DEPSC)" }.to_string()
+    } else if low_accuracy {
+        String::from("LS(DASH,1,DEPCN)")
+    } else {
+        String::from("LS(SOLD,1,DEPCN)")
+    }
+}
+
+/// A style expression matching the safety contour of the mariner's settings, the shallowest
+/// contour of a cell at or deeper than the safety contour
+pub fn is_safety_contour(settings: &MarinerSettings) -> Value {
+    json!(["all",
+        [">=", ["coalesce", ["get", "VALDCO"], ["get", "DRVAL1"], 0], settings.safety_contour],
+        ["<", ["coalesce", ["get", "CNTPRV"], -1000], settings.safety_contour]
+    ])
+}
+
+/// Contour value labels repeated along the contours in the depth units of the style. The safety
+/// contour is labelled at every zoom in its colour, the other contours only from `LABEL_MINZOOM`
+/// so small scale charts are not cluttered. Values are labelled in whole feet or to a tenth of a
+/// meter or fathom, the filters are
+/// expressions throughout as `is_safety_contour` is an expression.
+pub fn layers(colors: &Value, depth: &str, settings: &MarinerSettings) -> Vec<Value> {
+    let valdco = units::depth_expression("VALDCO", depth);
+    let precision = if depth == "feet" { 1 } else { 10 };
+    let safety_contour = is_safety_contour(settings);
+    let label = |id: &str, filter: Value, color: &Value| json!({
+        "id": id,
+        "type": "symbol",
+        "source": "src_senc",
+        "source-layer": "DEPCNT",
+        "filter": ["all", ["==", ["geometry-type"], "LineString"], ["has", "VALDCO"], filter],
+        "layout": {
+            "text-font": ["Roboto Bold"],
+            "text-field": ["to-string", ["/", ["round", ["*", valdco, precision]], precision]],
+            "text-size": 10,
+            "symbol-placement": "line",
+            "symbol-spacing": 300,
+            "text-rotation-alignment": "map",
+            "text-keep-upright": true,
+            "text-allow-overlap": false
+        },
+        "paint": {
+            "text-color": color,
+            "text-halo-color": colors["CHWHT"],
+            "text-halo-width": 1.5
+        }
+    });
+    let mut contours = label("DEPCNT_label", json!(["!", safety_contour]), &colors["DEPCN"]);
+    contours["minzoom"] = json!(LABEL_MINZOOM);
     vec![
-        json!({
-            "id": "depth_contoure",
-            "type": "line",
-            "source": "src_senc",
-            "source-layer": "DEPCNT",
-            "filter": ["all", ["==", "$type", "LineString"]],
-            "paint": {
-                "line-color": colors["CSTLN"],
-                "line-width": 0.5
-            }
-        })
+        contours,
+        label("DEPCNT_safety_label", safety_contour, &colors["DEPSC"]),
     ]
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use geojson::Value as GeoValue;
+    use crate::units::Units;
+
+    fn contour(value: Value) -> JsonObject {
+        value.as_object().unwrap().clone()
+    }
+
+    /// Evaluates the style expressions of the labels with the properties of a feature
+    fn evaluate(expression: &Value, properties: &JsonObject) -> Value {
+        let args = match expression.as_array() {
+            Some(args) => args,
+            None => return expression.clone(),
+        };
+        let number = |i: usize| evaluate(&args[i], properties).as_f64().unwrap();
+        match args[0].as_str().unwrap() {
+            "get" => properties[args[1].as_str().unwrap()].clone(),
+            "*" => json!(number(1) * number(2)),
+            "/" => json!(number(1) / number(2)),
+            "round" => json!(number(1).round()),
+            "to-string" => json!(number(1).to_string()),
+            operator => panic!("unsupported expression {}", operator),
+        }
+    }
+
+    #[test]
+    fn test_depcnt03() {
+        // contours of US5WA22M, the safety contour of 3m is displayed on the 3.6m contour
+        let mut context = Context::default();
+        for valdco in &[0.0, 1.8, 3.6, 5.4, 9.1] {
+            context.add("DEPCNT", &GeoValue::LineString(vec![]), &contour(json!({"VALDCO": valdco})));
+        }
+        let mut safety = contour(json!({"VALDCO": 3.6}));
+        assert_eq!("LS(SOLD,2,DEPSC)", depcnt03("DEPCNT", &mut safety, &context));
+        assert_eq!(json!(true), safety["SCNT"]);
+        assert_eq!(json!(1.8), safety["CNTPRV"]);
+        let mut shallow = contour(json!({"VALDCO": 1.8}));
+        assert_eq!("LS(SOLD,1,DEPCN)", depcnt03("DEPCNT", &mut shallow, &context));
+        assert!(!shallow.contains_key("SCNT"));
+        let mut approximate = contour(json!({"VALDCO": 5.4, "QUAPOS": 4}));
+        assert_eq!("LS(DASH,1,DEPCN)", depcnt03("DEPCNT", &mut approximate, &context));
+        let mut edge = contour(json!({"DRVAL1": 3.6}));
+        assert_eq!("LS(SOLD,2,DEPSC)", depcnt03("DEPARE", &mut edge, &context));
+    }
+
+    #[test]
+    fn test_layers() {
+        let colors = crate::colors::COLORS["DAY_BRIGHT"].clone();
+        let layers = layers(&colors, "feet", &MarinerSettings::default());
+        assert_eq!(2, layers.len());
+        let feet = json!(["*", ["get", "VALDCO"], 3.28084]);
+        assert_eq!(json!(["to-string", ["/", ["round", ["*", feet, 1]], 1]]), layers[0]["layout"]["text-field"]);
+        assert_eq!(json!(["==", ["geometry-type"], "LineString"]), layers[0]["filter"][1]);
+        assert_eq!(json!("line"), layers[0]["layout"]["symbol-placement"]);
+        assert_eq!(json!(LABEL_MINZOOM), layers[0]["minzoom"]);
+        assert!(layers[1].get("minzoom").is_none());
+        assert_eq!(colors["DEPSC"], layers[1]["paint"]["text-color"]);
+    }
+
+    #[test]
+    fn test_labels() {
+        let colors = crate::colors::COLORS["DAY_BRIGHT"].clone();
+        let label = |depth: &str, properties: &JsonObject| {
+            let layers = layers(&colors, depth, &MarinerSettings::default());
+            evaluate(&layers[0]["layout"]["text-field"], properties)
+        };
+        // a 30 ft contour of a cell in feet is stored in meters and labelled in feet again
+        let mut feet = contour(json!({"VALDCO": 30.0}));
+        Units { duni: Some(3), huni: None }.normalize(&mut feet);
+        assert_eq!(json!("30"), label("feet", &feet));
+        assert_eq!(json!("5"), label("fathoms", &feet));
+        assert_eq!(json!("9.1"), label("meters", &feet));
+        let mut fathoms = contour(json!({"VALDCO": 2.5}));
+        Units { duni: Some(4), huni: None }.normalize(&mut fathoms);
+        assert_eq!(json!("2.5"), label("fathoms", &fathoms));
+        assert_eq!(json!("15"), label("feet", &fathoms));
+    }
+}
@@ -3,14 +3,46 @@ use gdal::vector::FieldValue;
 use serde_json::{Value, Map, Number};
 use serde_json;
 use gdal::spatial_ref::SpatialRef;
-use crate::{soundg, boyspp, lights};
+use crate::relations::Relations;
+use crate::s57::ChartLayer;
+use crate::units::Units;
+use crate::dates;
+use crate::{soundg, boyspp, boylat, boycar, boyisd, boysaw, boyinb, bcnspp, bcnlat, bcncar, bcnisd, bcnsaw, tsslpt};
 
 pub type JsonObject = Map<String, Value>;
 
+/// A per class processor of a feature's geometry and properties
+pub type Processor = fn(geojson::Geometry, &mut JsonObject) -> geojson::Geometry;
+
+/// The processor of an object class
+fn processor(class: &str) -> Option<Processor> {
+    let processor: Processor = match class {
+        "SOUNDG" => soundg::process_sounding,
+        "BOYSPP" => boyspp::process_boyspp,
+        "BOYLAT" => boylat::process_boylat,
+        "BOYCAR" => boycar::process_boycar,
+        "BOYISD" => boyisd::process_boyisd,
+        "BOYSAW" => boysaw::process_boysaw,
+        "BOYINB" => boyinb::process_boyinb,
+        "BCNSPP" => bcnspp::process_bcnspp,
+        "BCNLAT" => bcnlat::process_bcnlat,
+        "BCNCAR" => bcncar::process_bcncar,
+        "BCNISD" => bcnisd::process_bcnisd,
+        "BCNSAW" => bcnsaw::process_bcnsaw,
+        "OBSTRN" | "UWTROC" | "WRECKS" => soundg::process_valsou,
+        "TSSLPT" | "TSSRON" | "RCTLPT" | "DWRTPT" | "TWRTPT" | "FAIRWY" | "RECTRC" | "NAVLNE" =>
+            tsslpt::process_orient,
+        _ => return None,
+    };
+    Some(processor)
+}
+
 fn gdal_feature_to_geojson_feature(
     feature: &gdal::vector::Feature,
+    mut properties: JsonObject,
     target_sr: &SpatialRef,
-    layer_name: &String
+    layer_name: &String,
+    units: &Units,
 ) -> Option<geojson::Feature> {
     feature.geometry_by_index(0).ok().and_then(|g| {
         if g.has_gdal_ptr() {
@@ -21,15 +53,14 @@ fn gdal_feature_to_geojson_feature(
                 g.json()
             }.ok().and_then(|json_str| {
                 let json_value = serde_json::from_str(json_str.as_str()).unwrap();
-                let mut properties = gdal_feature_properties(&feature);
+                units.normalize(&mut properties);
+                dates::normalize(&mut properties);
                 let geometry = geojson::Geometry::from_json_object(json_value)
-                    .map(|geojson_geom| {
-                        match layer_name.as_str() {
-                            "SOUNDG" => soundg::process_sounding(geojson_geom, &mut properties),
-                            "BOYSPP" => boyspp::process_boyspp(geojson_geom, &mut properties),
-                            "LIGHTS" => lights::process_lights(geojson_geom, &mut properties),
-                            _ => geojson_geom
+                    .map(|mut geojson_geom| {
+                        if layer_name == "SOUNDG" {
+                            units.normalize_sounding(&mut geojson_geom);
                         }
+                        geojson_geom
                     })
                     .ok();
                 Some(geojson::Feature {
@@ -67,7 +98,7 @@ fn field_value_to_json_value(fv: &FieldValue) -> Option<Value> {
     }
 }
 
-fn gdal_feature_properties(feature: &gdal::vector::Feature) -> JsonObject {
+pub fn gdal_feature_properties(feature: &gdal::vector::Feature) -> JsonObject {
     let mut props = JsonObject::new();
     feature.fields().for_each(|each| {
         if let Some(v) = field_value_to_json_value(&each.1) {
@@ -78,10 +109,24 @@ fn gdal_feature_properties(feature: &gdal::vector::Feature) -> JsonObject {
     props
 }
 
-pub fn feature_collection_from_layer(layer: &gdal::vector::Layer, target_sr: &SpatialRef) -> Option<geojson::FeatureCollection> {
-    let features: Vec<geojson::Feature> = layer.features().into_iter().map(|f| {
-        gdal_feature_to_geojson_feature(&f, target_sr, &layer.name())
-    }).filter(|f| f.is_some()).map(|f| f.unwrap()).collect();
+/// Converts the features of the layer. Depths and heights are converted from the cell's units to
+/// meters and dates to ISO 8601 dates, the features are processed by `process_layers`. Every
+/// feature of the layer is indexed in the relations when they are given, including the features
+/// without a geometry eg the collection objects C_AGGR and C_ASSO.
+pub fn feature_collection_from_layer(
+    layer: &gdal::vector::Layer,
+    target_sr: &SpatialRef,
+    units: &Units,
+    mut relations: Option<&mut Relations>,
+) -> Option<geojson::FeatureCollection> {
+    let name = layer.name();
+    let features: Vec<geojson::Feature> = layer.features().into_iter().filter_map(|f| {
+        let properties = gdal_feature_properties(&f);
+        if let Some(relations) = relations.as_mut() {
+            relations.add(&name, &properties);
+        }
+        gdal_feature_to_geojson_feature(&f, properties, target_sr, &name, units)
+    }).collect();
 
     if features.is_empty() {
         None
@@ -94,6 +139,23 @@ pub fn feature_collection_from_layer(layer: &gdal::vector::Layer, target_sr: &Sp
     }
 }
 
+/// Runs the processor of every layer's object class over its features. The feature relationships
+/// `PARENT_LNAM` and `CHILDREN` of the cell are written to the properties when `annotate` is set.
+pub fn process_layers(layers: &mut [ChartLayer], relations: &Relations, annotate: bool) {
+    for layer in layers.iter_mut() {
+        let processor = processor(&layer.name);
+        for feature in layer.collection.features.iter_mut() {
+            let properties = feature.properties.get_or_insert_with(JsonObject::new);
+            if annotate {
+                relations.annotate(properties);
+            }
+            if let (Some(processor), Some(geometry)) = (processor, feature.geometry.take()) {
+                feature.geometry = Some(processor(geometry, properties));
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -113,7 +175,7 @@ mod test {
         let layer = ds.layer_by_name(name.as_str()).unwrap();
         let feat = layer.features().nth(0).unwrap();
         let sr = SpatialRef::from_epsg(4326).unwrap();
-        let gf = gdal_feature_to_geojson_feature(&feat, &sr, &name);
+        let gf = gdal_feature_to_geojson_feature(&feat, gdal_feature_properties(&feat), &sr, &name, &Units::default());
         assert!(gf.is_some())
     }
 }
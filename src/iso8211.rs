@@ -0,0 +1,248 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// ISO/IEC 8211 reader, the encapsulation used by S57 exchange set files such as CATALOG.031
+/// https://iho.int/uploads/user/pubs/standards/s-57/31Main.pdf (Part 3, Annex A)
+///
+/// Each record starts with a 24 byte leader followed by a directory of (tag, length, position)
+/// entries and the field area. The first record is the data descriptive record (DDR) describing
+/// the subfield labels and formats of every field, the data records (DR) that follow hold values.
+const LEADER_LEN: usize = 24;
+const UNIT_TERMINATOR: u8 = 0x1f;
+const FIELD_TERMINATOR: u8 = 0x1e;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum SubfieldFormat {
+    /// A, I and R character data with an optional fixed width, otherwise unit terminated
+    Text(Option<usize>),
+    /// b1w unsigned and b2w signed little endian integers of w bytes
+    Unsigned(usize),
+    Signed(usize),
+}
+
+struct FieldDefinition {
+    labels: Vec<String>,
+    formats: Vec<SubfieldFormat>,
+}
+
+pub struct Record {
+    fields: Vec<(String, Vec<u8>)>,
+}
+
+pub struct Iso8211File {
+    definitions: HashMap<String, FieldDefinition>,
+    pub records: Vec<Record>,
+}
+
+impl Iso8211File {
+    pub fn open(path: &Path) -> Option<Iso8211File> {
+        fs::read(path).ok().and_then(|bytes| Iso8211File::read(&bytes))
+    }
+
+    pub fn read(bytes: &[u8]) -> Option<Iso8211File> {
+        let mut records = vec![];
+        let mut offset = 0;
+        while offset + LEADER_LEN <= bytes.len() {
+            let (record, len) = read_record(&bytes[offset..])?;
+            records.push(record);
+            offset += len;
+        }
+        if records.is_empty() {
+            return None;
+        }
+        let ddr = records.remove(0);
+        let definitions = ddr.fields.iter()
+            .filter_map(|(tag, data)| read_field_definition(data).map(|d| (tag.clone(), d)))
+            .collect();
+        Some(Iso8211File { definitions, records })
+    }
+
+    /// The labeled subfield values of the first occurrence of the field `tag` in the record
+    pub fn subfields(&self, record: &Record, tag: &str) -> Option<HashMap<String, String>> {
+        let definition = self.definitions.get(tag)?;
+        let data = record.fields.iter().find(|f| f.0 == tag).map(|f| &f.1)?;
+        let mut values = HashMap::new();
+        let mut pos = 0;
+        for (label, format) in definition.labels.iter().zip(definition.formats.iter()) {
+            if pos >= data.len() {
+                break;
+            }
+            let (value, len) = match format {
+                SubfieldFormat::Text(Some(width)) => {
+                    let end = (pos + width).min(data.len());
+                    (String::from_utf8_lossy(&data[pos..end]).trim().to_string(), end - pos)
+                }
+                SubfieldFormat::Text(None) => {
+                    let end = data[pos..].iter()
+                        .position(|b| *b == UNIT_TERMINATOR || *b == FIELD_TERMINATOR)
+                        .map(|p| pos + p)
+                        .unwrap_or(data.len());
+                    (String::from_utf8_lossy(&data[pos..end]).trim().to_string(), end - pos + 1)
+                }
+                SubfieldFormat::Unsigned(width) | SubfieldFormat::Signed(width) => {
+                    let end = (pos + width).min(data.len());
+                    let mut n = 0_u64;
+                    for (i, b) in data[pos..end].iter().enumerate() {
+                        n |= (*b as u64) << (8 * i);
+                    }
+                    let value = if let SubfieldFormat::Signed(_) = format {
+                        let shift = 64 - 8 * (end - pos) as u32;
+                        (((n << shift) as i64) >> shift).to_string()
+                    } else {
+                        n.to_string()
+                    };
+                    (value, end - pos)
+                }
+            };
+            values.insert(label.clone(), value);
+            pos += len;
+        }
+        Some(values)
+    }
+}
+
+fn ascii_number(bytes: &[u8]) -> Option<usize> {
+    std::str::from_utf8(bytes).ok().and_then(|s| s.trim().parse().ok())
+}
+
+/// Reads a record, returning it and its length in bytes. None when the leader or directory does
+/// not fit in the record eg a truncated or corrupt file.
+fn read_record(bytes: &[u8]) -> Option<(Record, usize)> {
+    let leader = bytes.get(0..LEADER_LEN)?;
+    let record_len = ascii_number(&leader[0..5])?;
+    let base_address = ascii_number(&leader[12..17])?;
+    let size_len = ascii_number(&leader[20..21])?;
+    let size_pos = ascii_number(&leader[21..22])?;
+    let size_tag = ascii_number(&leader[23..24])?;
+    let entry_len = size_tag + size_len + size_pos;
+    if record_len < LEADER_LEN || base_address > record_len || entry_len == 0 {
+        return None;
+    }
+    let record = bytes.get(0..record_len)?;
+    let mut fields = vec![];
+    let mut entry = LEADER_LEN;
+    while entry + entry_len <= base_address && *record.get(entry)? != FIELD_TERMINATOR {
+        let tag = String::from_utf8_lossy(record.get(entry..entry + size_tag)?).to_string();
+        let len = ascii_number(record.get(entry + size_tag..entry + size_tag + size_len)?)?;
+        let pos = ascii_number(record.get(entry + size_tag + size_len..entry + entry_len)?)?;
+        let data = record.get(base_address + pos..base_address + pos + len)?;
+        fields.push((tag, data.to_vec()));
+        entry += entry_len;
+    }
+    Some((Record { fields }, record_len))
+}
+
+/// Data descriptive field: field controls; name UT array descriptor UT format controls FT
+fn read_field_definition(data: &[u8]) -> Option<FieldDefinition> {
+    let parts: Vec<&[u8]> = data.split(|b| *b == UNIT_TERMINATOR || *b == FIELD_TERMINATOR).collect();
+    let descriptor = String::from_utf8_lossy(parts.get(1)?).to_string();
+    let controls = String::from_utf8_lossy(parts.get(2)?).to_string();
+    let labels: Vec<String> = descriptor.trim_start_matches('*')
+        .split('!')
+        .filter(|l| !l.is_empty())
+        .map(String::from)
+        .collect();
+    if labels.is_empty() {
+        return None;
+    }
+    Some(FieldDefinition { labels, formats: parse_formats(&controls) })
+}
+
+/// Expands format controls such as `(A(2),I(10),3A,2b11)` into one format per subfield, nested
+/// repeating groups are flattened.
+fn parse_formats(controls: &str) -> Vec<SubfieldFormat> {
+    let mut formats = vec![];
+    let inner = controls.trim().trim_start_matches('(').trim_end_matches(')');
+    for item in inner.split(',').map(|i| i.trim_matches(|c| c == '(' || c == ')' || c == ' ')) {
+        let digits: String = item.chars().take_while(|c| c.is_ascii_digit()).collect();
+        let repeat: usize = digits.parse().unwrap_or(1);
+        let spec = &item[digits.len()..];
+        let format = if spec.starts_with('b') {
+            let kind = spec.chars().nth(1).and_then(|c| c.to_digit(10)).unwrap_or(1);
+            let width = spec.chars().nth(2).and_then(|c| c.to_digit(10)).unwrap_or(1) as usize;
+            if kind == 2 {
+                SubfieldFormat::Signed(width)
+            } else {
+                SubfieldFormat::Unsigned(width)
+            }
+        } else if spec.is_empty() {
+            continue;
+        } else {
+            let width = spec.find('(')
+                .and_then(|start| spec[start + 1..].trim_end_matches(')').parse().ok())
+                .or_else(|| spec[1..].parse().ok());
+            SubfieldFormat::Text(width)
+        };
+        (0..repeat).for_each(|_| formats.push(format));
+    }
+    formats
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+
+    /// Assembles an ISO 8211 record from (tag, data) fields
+    pub fn record(leader_id: char, fields: &[(&str, Vec<u8>)]) -> Vec<u8> {
+        let mut directory = vec![];
+        let mut field_area = vec![];
+        for (tag, data) in fields {
+            directory.extend_from_slice(format!("{}{:03}{:04}", tag, data.len(), field_area.len()).as_bytes());
+            field_area.extend_from_slice(data);
+        }
+        directory.push(FIELD_TERMINATOR);
+        let base_address = LEADER_LEN + directory.len();
+        let record_len = base_address + field_area.len();
+        let field_control_len = if leader_id == 'L' { "09" } else { "  " };
+        let mut bytes = format!("{:05}3{}E1 {}{:05} ! 3404", record_len, leader_id, field_control_len, base_address).into_bytes();
+        bytes.extend_from_slice(&directory);
+        bytes.extend_from_slice(&field_area);
+        bytes
+    }
+
+    pub fn field_definition(name: &str, descriptor: &str, controls: &str) -> Vec<u8> {
+        format!("1600;&   {}\x1f{}\x1f{}\x1e", name, descriptor, controls).into_bytes()
+    }
+
+    #[test]
+    fn test_parse_formats() {
+        assert_eq!(
+            vec![SubfieldFormat::Text(Some(2)), SubfieldFormat::Text(Some(10)), SubfieldFormat::Text(None),
+                 SubfieldFormat::Text(None), SubfieldFormat::Unsigned(1), SubfieldFormat::Signed(4)],
+            parse_formats("(A(2),I(10),2A,b11,b24)")
+        );
+    }
+
+    #[test]
+    fn test_read() {
+        let mut bytes = record('L', &[
+            ("0001", field_definition("ISO 8211 Record Identifier", "", "")),
+            ("CATD", field_definition("Catalogue Directory", "RCNM!RCID!FILE!LFIL", "(A(2),I(10),2A)")),
+        ]);
+        bytes.extend(record('D', &[
+            ("0001", vec![1, 0, FIELD_TERMINATOR]),
+            ("CATD", b"CD0000000001US5WA22M\\US5WA22M.000\x1f\x1f\x1e".to_vec()),
+        ]));
+        let file = Iso8211File::read(&bytes).unwrap();
+        assert_eq!(1, file.records.len());
+        let catd = file.subfields(&file.records[0], "CATD").unwrap();
+        assert_eq!("CD", catd["RCNM"]);
+        assert_eq!("0000000001", catd["RCID"]);
+        assert_eq!("US5WA22M\\US5WA22M.000", catd["FILE"]);
+        assert_eq!("", catd["LFIL"]);
+    }
+
+    #[test]
+    fn test_read_corrupt() {
+        let bytes = record('L', &[
+            ("0001", field_definition("ISO 8211 Record Identifier", "", "")),
+        ]);
+        // truncated record
+        assert!(Iso8211File::read(&bytes[..bytes.len() - 4]).is_none());
+        // base address of the field area beyond the end of the record
+        let mut corrupt = bytes.clone();
+        corrupt[12..17].copy_from_slice(b"99999");
+        assert!(read_record(&corrupt).is_none());
+    }
+}
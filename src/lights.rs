@@ -1,44 +1,23 @@
-use serde_json::json;
-use serde_json::Value;
-use geojson::Geometry;
+use std::collections::BTreeMap;
+use serde_json::{Value, json};
+use geojson::Value as GeoValue;
 use crate::geojson_builder::JsonObject;
 use crate::colors::Colour;
+use crate::csp::{self, Context};
+use crate::s57::ChartLayer;
+use crate::tiler;
 use crate::util::compare;
 
-/// LIGHTS, Light
-/// Geometric primitives: P
-///
-/// Set Attribute_A: 	(!?)CATLIT; (!?)COLOUR; DATEND; DATSTA; EXCLIT; (?)HEIGHT; (!?)LITCHR; LITVIS; MARSYS; MLTYLT; NOBJNM; OBJNAM; (!?)ORIENT; PEREND; PERSTA; (!?)SECTR1; (!?)SECTR2; (!?)SIGGRP; (!?)SIGPER; (?)SIGSEQ; STATUS; VALNMR; VERACC; (?)VERDAT;
-/// Set Attribute_B: 	INFORM; NINFOM; NTXTDS; SCAMAX; SCAMIN; TXTDSC;
-/// Set Attribute_C: 	RECDAT; RECIND; SORDAT; SORIND;
-///
-/// Definition:
-///     A luminous or lighted aid to navigation. (adapted from IHO Dictionary, S-32, 5th Edition, 2766)
-/// References
-///     INT 1:	IP 1-30.3, 40-65;
-///     S-4:	470-473.5; 475-475.7; 476-478,5;
-/// Remarks:
-///     A light may be fixed on a buoy, beacon, tower etc. These are separate objects.
-/// Distinction:
-///     beacon, cardinal; beacon, isolated danger; beacon, lateral; beacon, safe water; beacon special purpose/general; buoy, cardinal; buoy, installation; buoy, isolated danger; buoy, lateral; buoy, safe water; buoy, special purpose/general; light vessel; light float;
-pub fn layers() -> Vec<Value> {
-    vec![
-        json!({
-            "id": "lights",
-            "type": "symbol",
-            "source": "src_senc",
-            "source-layer": "LIGHTS",
-            "filter": [ "any", [ "==", "$type", "Point" ] ],
-            "layout": {
-                "icon-image": ["get", "SY"],
-                "icon-keep-upright": false,
-                "icon-anchor": "top-left",
-                "icon-allow-overlap": true,
-                "symbol-placement": "point"
-            }
-        })
-    ]
-}
+/// The tile layer of light sector legs, arcs and directional light orientation lines
+pub const SECTORS_LAYER: &str = "LIGHTS_SECTORS";
+/// Sector legs and orientation lines are 25mm long and arcs have a 20mm radius on screen
+const LEG_LENGTH_MM: f64 = 25.0;
+const ARC_RADIUS_MM: f64 = 20.0;
+/// Screen sized geometry is generated for each zoom level of this range, the last one is used
+/// for deeper zoom levels
+const SECTOR_ZOOMS: std::ops::RangeInclusive<u8> = 6..=18;
+const METERS_PER_NM: f64 = 1852.0;
+const EARTH_RADIUS: f64 = 6_371_008.8;
 
 ///Attribute type: L 	Used in: 	LIGHTS
 ///
@@ -66,6 +45,7 @@ pub fn layers() -> Vec<Value> {
 ///     20	vertically disposed
 /// Remarks:
 ///     Marine light (a light intended primarily for marine navigation) is not included in the above list. All lights are considered to be marine lights unless the attribute 'category of light' indicates otherwise.
+#[derive(PartialEq)]
 pub enum Catlit {
     DirectionalFunction,
     RearUpperLight,
@@ -132,21 +112,480 @@ impl Catlit {
     }
 }
 
-pub fn process_lights(geojson_geom: Geometry, properties: &mut JsonObject) -> Geometry {
+/// LIGHTS, Light
+/// Geometric primitives: P
+///
+/// Set Attribute_A: 	(!?)CATLIT; (!?)COLOUR; DATEND; DATSTA; EXCLIT; (?)HEIGHT; (!?)LITCHR; LITVIS; MARSYS; MLTYLT; NOBJNM; OBJNAM; (!?)ORIENT; PEREND; PERSTA; (!?)SECTR1; (!?)SECTR2; (!?)SIGGRP; (!?)SIGPER; (?)SIGSEQ; STATUS; VALNMR; VERACC; (?)VERDAT;
+/// Set Attribute_B: 	INFORM; NINFOM; NTXTDS; SCAMAX; SCAMIN; TXTDSC;
+/// Set Attribute_C: 	RECDAT; RECIND; SORDAT; SORIND;
+///
+/// Definition:
+///     A luminous or lighted aid to navigation. (adapted from IHO Dictionary, S-32, 5th Edition, 2766)
+/// References
+///     INT 1:	IP 1-30.3, 40-65;
+///     S-4:	470-473.5; 475-475.7; 476-478,5;
+/// Remarks:
+///     A light may be fixed on a buoy, beacon, tower etc. These are separate objects.
+/// Distinction:
+///     beacon, cardinal; beacon, isolated danger; beacon, lateral; beacon, safe water; beacon special purpose/general; buoy, cardinal; buoy, installation; buoy, isolated danger; buoy, lateral; buoy, safe water; buoy, special purpose/general; light vessel; light float;
+///
+/// Conditional symbology procedure LIGHTS06
+///
+/// Flood, strip and spot lights have their own symbols, other lights are drawn with a light flare
+/// in the colour of the light. The flare points at 135 degrees and is turned to 45 degrees when a
+/// coloured light shares its position with other lights so that the flares do not overlap.
+/// Sector lights are flagged with `SECT` and their sector colour `LITC`. The light description is
+/// kept in `LITDSN` with the height in meters and `LITDSN_FT` with the height in feet.
+pub fn lights06(geometry: &GeoValue, properties: &mut JsonObject, context: &Context) -> String {
     let catlit = Catlit::from_value(properties);
-    let colors = Colour::from_value(properties);
-
-    //todo: (WK) WIP
-    let symbol = if compare(&vec![Colour::Red], &colors) {
-        "LIGHTS11"
-    } else if compare(&vec![Colour::Green], &colors) {
-        "LIGHTS12"
-    } else if compare(&vec![Colour::Yellow], &colors) {
-        "LIGHTS13"
+    if catlit.contains(&Catlit::FloodLight) {
+        return String::from("SY(LIGHTS82)");
+    }
+    if catlit.contains(&Catlit::StripLight) || catlit.contains(&Catlit::Spotlight) {
+        return String::from("SY(LIGHTS81)");
+    }
+    let colours = Colour::from_value(properties);
+    let (symbol, sector_colour, coloured) = if compare(&[Colour::Red], &colours) || compare(&[Colour::Red, Colour::White], &colours) {
+        ("LIGHTS11", "LITRD", true)
+    } else if compare(&[Colour::Green], &colours) || compare(&[Colour::Green, Colour::White], &colours) {
+        ("LIGHTS12", "LITGN", true)
+    } else if compare(&[Colour::White], &colours) || compare(&[Colour::Yellow], &colours) || compare(&[Colour::Orange], &colours) {
+        ("LIGHTS13", "LITYW", false)
     } else {
-        "LIGHTDEF"
+        ("LITDEF11", "CHMGD", false)
+    };
+    if properties.contains_key("SECTR1") && properties.contains_key("SECTR2") {
+        properties.insert(String::from("SECT"), Value::Bool(true));
+        properties.insert(String::from("LITC"), Value::String(String::from(sector_colour)));
+    }
+    let shared = match geometry {
+        GeoValue::Point(position) => !context.other_lights(position, properties.get("COLOUR")).is_empty(),
+        _ => false,
     };
+    let rotation = if coloured && shared { 45 } else { 135 };
+    if let Some(description) = litdsn01(properties, false) {
+        properties.insert(String::from(LITDSN), Value::String(description));
+    }
+    if let Some(description) = litdsn01(properties, true) {
+        properties.insert(String::from(LITDSN_FT), Value::String(description));
+    }
+    format!("SY({},{})", symbol, rotation)
+}
 
-    properties.insert(String::from("SY"), Value::String(String::from(symbol)));
-    geojson_geom
-}
\ No newline at end of file
+static LITDSN: &str = "LITDSN";
+static LITDSN_FT: &str = "LITDSN_FT";
+
+/// LITCHR, Light characteristic abbreviations (INT 1 IP 10.1 - 10.11)
+fn light_characteristic(litchr: i64) -> Option<&'static str> {
+    match litchr {
+        1 => Some("F"),
+        2 => Some("Fl"),
+        3 => Some("LFl"),
+        4 => Some("Q"),
+        5 => Some("VQ"),
+        6 => Some("UQ"),
+        7 => Some("Iso"),
+        8 => Some("Oc"),
+        9 => Some("IQ"),
+        10 => Some("IVQ"),
+        11 => Some("IUQ"),
+        12 => Some("Mo"),
+        13 => Some("FFl"),
+        14 => Some("Fl+LFl"),
+        15 => Some("Oc+Fl"),
+        16 => Some("FLFl"),
+        17 => Some("Al.Oc"),
+        18 => Some("Al.LFl"),
+        19 => Some("Al.Fl"),
+        20 | 28 => Some("Al"),
+        25 => Some("Q+LFl"),
+        26 => Some("VQ+LFl"),
+        27 => Some("UQ+LFl"),
+        29 => Some("Al.FFl"),
+        _ => None,
+    }
+}
+
+/// COLOUR abbreviations of light colours (INT 1 IP 11.1 - 11.8)
+fn light_colour(colour: i64) -> &'static str {
+    match colour {
+        1 => "W",
+        3 => "R",
+        4 => "G",
+        5 => "Bu",
+        6 => "Y",
+        9 => "Am",
+        10 => "Vi",
+        11 => "Or",
+        _ => "",
+    }
+}
+
+/// A whole number without a fraction eg 6 and a fraction otherwise eg 2.5
+fn format_number(value: f64) -> String {
+    if value.fract().abs() < f64::EPSILON {
+        format!("{}", value as i64)
+    } else {
+        format!("{}", value)
+    }
+}
+
+/// LITDSN01, the abbreviated light description eg `Fl(2) R 6s 12m 5M`
+///     LITCHR and SIGGRP - characteristic and signal group, `()` and `(1)` are omitted
+///     COLOUR - colours, eg WR for an alternating white and red light
+///     SIGPER - period in seconds
+///     HEIGHT - elevation in meters or feet
+///     VALNMR - nominal range in nautical miles
+pub fn litdsn01(properties: &JsonObject, feet: bool) -> Option<String> {
+    let litchr = csp::list_values(properties, "LITCHR").first()
+        .and_then(|litchr| light_characteristic(*litchr));
+    let mut parts: Vec<String> = vec![];
+    if let Some(litchr) = litchr {
+        let group = properties.get("SIGGRP").and_then(|v| v.as_str()).unwrap_or("");
+        match group {
+            "" | "()" | "(1)" => parts.push(String::from(litchr)),
+            group => parts.push(format!("{}{}", litchr, group)),
+        }
+    }
+    let colours: String = csp::list_values(properties, "COLOUR").into_iter().map(light_colour).collect();
+    if !colours.is_empty() {
+        parts.push(colours);
+    }
+    let number = |key: &str| properties.get(key).and_then(|v| v.as_f64());
+    if let Some(sigper) = number("SIGPER") {
+        parts.push(format!("{}s", format_number(sigper)));
+    }
+    if let Some(height) = number("HEIGHT") {
+        if feet {
+            parts.push(format!("{}ft", (height * 3.28084).round() as i64));
+        } else {
+            parts.push(format!("{}m", format_number((height * 10.0).round() / 10.0)));
+        }
+    }
+    if let Some(valnmr) = number("VALNMR") {
+        parts.push(format!("{}M", format_number(valnmr)));
+    }
+    if litchr.is_none() || parts.is_empty() {
+        None
+    } else {
+        Some(parts.join(" "))
+    }
+}
+
+/// Light sector geometry, S-52 PresLib 13.2.11 (LIGHTS06)
+///
+/// Sector limits SECTR1 and SECTR2 are bearings from seaward towards the light so legs are drawn
+/// from the light in the opposite direction. Each feature has a `KIND`
+///     leg - a sector limit
+///     arc - the sector in the light's colour `LITC`
+///     orient - the direction of a directional light (ORIENT)
+///     range - the sector arc at the nominal range VALNMR
+/// Legs, arcs and orientation lines keep their size on screen so they are generated for every
+/// zoom level and limited to it with the tippecanoe foreign member.
+pub fn sector_layer(layers: &[ChartLayer]) -> Option<ChartLayer> {
+    let lights = layers.iter().find(|l| l.name == "LIGHTS")?;
+    let features: Vec<geojson::Feature> = lights.collection.features.iter()
+        .filter_map(|f| match (&f.geometry, &f.properties) {
+            (Some(geojson::Geometry { value: GeoValue::Point(p), .. }), Some(properties)) => {
+                Some(sector_features(p, properties))
+            }
+            _ => None,
+        })
+        .flatten()
+        .collect();
+    if features.is_empty() {
+        return None;
+    }
+    let fields: BTreeMap<String, String> = vec![
+        ("KIND", "String"), ("LITC", "String"), ("SECTR1", "Number"), ("SECTR2", "Number"),
+        ("ORIENT", "Number"), ("VALNMR", "Number"),
+    ].into_iter().map(|(k, v)| (String::from(k), String::from(v))).collect();
+    Some(ChartLayer {
+        name: String::from(SECTORS_LAYER),
+        fields,
+        collection: geojson::FeatureCollection { bbox: None, features, foreign_members: None },
+    })
+}
+
+fn sector_features(position: &[f64], light: &JsonObject) -> Vec<geojson::Feature> {
+    let number = |key: &str| light.get(key).and_then(|v| v.as_f64());
+    let colour = light.get("LITC").and_then(|v| v.as_str()).unwrap_or("CHMGD");
+    let valnmr = number("VALNMR");
+    let mut features = vec![];
+    let mut properties = JsonObject::new();
+    properties.insert(String::from("LITC"), json!(colour));
+    if let Some(valnmr) = valnmr {
+        properties.insert(String::from("VALNMR"), json!(valnmr));
+    }
+    let lat = position[1];
+    let last_zoom = *SECTOR_ZOOMS.end();
+    if let (Some(sectr1), Some(sectr2)) = (number("SECTR1"), number("SECTR2")) {
+        properties.insert(String::from("SECTR1"), json!(sectr1));
+        properties.insert(String::from("SECTR2"), json!(sectr2));
+        let start = sectr1 + 180.0;
+        let mut sweep = (sectr2 - sectr1).rem_euclid(360.0);
+        let all_round = sweep == 0.0;
+        if all_round {
+            sweep = 360.0;
+        }
+        for zoom in SECTOR_ZOOMS {
+            let leg_length = tiler::screen_distance(LEG_LENGTH_MM, zoom, lat);
+            if !all_round {
+                for bearing in &[start, start + sweep] {
+                    let leg = vec![position.to_vec(), destination(position, *bearing, leg_length)];
+                    features.push(feature("leg", GeoValue::LineString(leg), &properties, zoom, zoom == last_zoom));
+                }
+            }
+            let radius = tiler::screen_distance(ARC_RADIUS_MM, zoom, lat);
+            features.push(feature("arc", arc(position, start, sweep, radius), &properties, zoom, zoom == last_zoom));
+        }
+        if let Some(valnmr) = valnmr {
+            let mut range = feature("range", arc(position, start, sweep, valnmr * METERS_PER_NM), &properties, 0, true);
+            range.foreign_members = None;
+            features.push(range);
+        }
+    } else if let (Some(orient), true) = (number("ORIENT"), Catlit::from_value(light).contains(&Catlit::DirectionalFunction)) {
+        properties.insert(String::from("ORIENT"), json!(orient));
+        for zoom in SECTOR_ZOOMS {
+            let length = valnmr.map(|v| v * METERS_PER_NM)
+                .unwrap_or_else(|| tiler::screen_distance(LEG_LENGTH_MM, zoom, lat));
+            let line = vec![position.to_vec(), destination(position, orient + 180.0, length)];
+            features.push(feature("orient", GeoValue::LineString(line), &properties, zoom, zoom == last_zoom));
+        }
+    }
+    features
+}
+
+fn feature(kind: &str, value: GeoValue, properties: &JsonObject, zoom: u8, last: bool) -> geojson::Feature {
+    let mut properties = properties.clone();
+    properties.insert(String::from("KIND"), json!(kind));
+    let tippecanoe = if last {
+        json!({"minzoom": zoom})
+    } else {
+        json!({"minzoom": zoom, "maxzoom": zoom})
+    };
+    geojson::Feature {
+        bbox: None,
+        geometry: Some(geojson::Geometry::new(value)),
+        id: None,
+        properties: Some(properties),
+        foreign_members: Some(json!({"tippecanoe": tippecanoe}).as_object().unwrap().clone()),
+    }
+}
+
+/// An arc clockwise from the bearing with a vertex every 2 degrees
+fn arc(center: &[f64], start: f64, sweep: f64, radius: f64) -> GeoValue {
+    let steps = (sweep / 2.0).ceil().max(1.0) as usize;
+    GeoValue::LineString((0..=steps).map(|i| {
+        destination(center, start + sweep * i as f64 / steps as f64, radius)
+    }).collect())
+}
+
+/// The position at the distance in meters along the great circle bearing from the origin
+fn destination(origin: &[f64], bearing: f64, distance: f64) -> Vec<f64> {
+    let (lon1, lat1) = (origin[0].to_radians(), origin[1].to_radians());
+    let (theta, delta) = (bearing.to_radians(), distance / EARTH_RADIUS);
+    let lat2 = (lat1.sin() * delta.cos() + lat1.cos() * delta.sin() * theta.cos()).asin();
+    let lon2 = lon1 + (theta.sin() * delta.sin() * lat1.cos()).atan2(delta.cos() - lat1.sin() * lat2.sin());
+    vec![lon2.to_degrees(), lat2.to_degrees()]
+}
+
+/// Style layers of the light sectors and light descriptions, the nominal range arcs are hidden
+/// until a style enables them. Heights are described in feet on feet and fathoms styles.
+pub fn layers(colors: &Value, depth: &str) -> Vec<Value> {
+    let description = match depth {
+        "meters" => LITDSN,
+        _ => LITDSN_FT,
+    };
+    let sector_colour = json!(["match", ["get", "LITC"],
+        "LITRD", colors["LITRD"],
+        "LITGN", colors["LITGN"],
+        "LITYW", colors["LITYW"],
+        colors["CHMGD"]
+    ]);
+    vec![
+        json!({
+            "id": "LIGHTS_SECTORS_range",
+            "type": "line",
+            "source": "src_senc",
+            "source-layer": SECTORS_LAYER,
+            "filter": ["==", "KIND", "range"],
+            "layout": {
+                "visibility": "none"
+            },
+            "paint": {
+                "line-color": sector_colour,
+                "line-width": 1,
+                "line-dasharray": [4, 2]
+            }
+        }),
+        json!({
+            "id": "LIGHTS_SECTORS_leg",
+            "type": "line",
+            "source": "src_senc",
+            "source-layer": SECTORS_LAYER,
+            "filter": ["in", "KIND", "leg", "orient"],
+            "paint": {
+                "line-color": colors["CHBLK"],
+                "line-width": 1,
+                "line-dasharray": [4, 2]
+            }
+        }),
+        json!({
+            "id": "LIGHTS_SECTORS_arc_outline",
+            "type": "line",
+            "source": "src_senc",
+            "source-layer": SECTORS_LAYER,
+            "filter": ["==", "KIND", "arc"],
+            "paint": {
+                "line-color": colors["CHBLK"],
+                "line-width": 4
+            }
+        }),
+        json!({
+            "id": "LIGHTS_SECTORS_arc",
+            "type": "line",
+            "source": "src_senc",
+            "source-layer": SECTORS_LAYER,
+            "filter": ["==", "KIND", "arc"],
+            "paint": {
+                "line-color": sector_colour,
+                "line-width": 2
+            }
+        }),
+        json!({
+            "id": "LIGHTS_description",
+            "type": "symbol",
+            "source": "src_senc",
+            "source-layer": "LIGHTS",
+            "filter": ["has", description],
+            "layout": {
+                "text-font": ["Roboto Bold"],
+                "text-field": ["get", description],
+                "text-size": 10,
+                "text-anchor": "left",
+                "text-offset": [1.5, 0.5],
+                "text-allow-overlap": false,
+                "symbol-placement": "point"
+            },
+            "paint": {
+                "text-color": colors["CHBLK"],
+                "text-halo-color": *crate::colors::TXT_FG,
+                "text-halo-width": 1
+            }
+        }),
+    ]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    fn light(value: Value) -> JsonObject {
+        value.as_object().unwrap().clone()
+    }
+
+    #[test]
+    fn test_lights06() {
+        // lights of US5WA22M are green or white
+        let point = GeoValue::Point(vec![-122.4, 47.3]);
+        let mut context = Context::default();
+        let mut green = light(json!({"COLOUR": ["4"], "LITCHR": 2}));
+        assert_eq!("SY(LIGHTS12,135)", lights06(&point, &mut green, &context));
+        let mut white = light(json!({"COLOUR": ["1"]}));
+        assert_eq!("SY(LIGHTS13,135)", lights06(&point, &mut white, &context));
+        let mut red = light(json!({"COLOUR": ["3"]}));
+        assert_eq!("SY(LIGHTS11,135)", lights06(&point, &mut red, &context));
+        let mut flood = light(json!({"CATLIT": ["8"], "COLOUR": ["1"]}));
+        assert_eq!("SY(LIGHTS82)", lights06(&point, &mut flood, &context));
+
+        context.add("LIGHTS", &point, &light(json!({"COLOUR": ["1"]})));
+        context.add("LIGHTS", &point, &light(json!({"COLOUR": ["3"]})));
+        assert_eq!("SY(LIGHTS11,45)", lights06(&point, &mut red, &context));
+
+        let mut sector = light(json!({"COLOUR": ["4"], "SECTR1": 120.0, "SECTR2": 240.0}));
+        lights06(&point, &mut sector, &Context::default());
+        assert_eq!(json!(true), sector["SECT"]);
+        assert_eq!(json!("LITGN"), sector["LITC"]);
+    }
+
+    #[test]
+    fn test_destination() {
+        let north = destination(&[-122.4, 47.3], 0.0, METERS_PER_NM);
+        assert!((north[0] + 122.4).abs() < 1e-9);
+        assert!((north[1] - 47.3 - 1.0 / 60.0).abs() < 1e-4);
+        let east = destination(&[0.0, 0.0], 90.0, METERS_PER_NM);
+        assert!((east[0] - 1.0 / 60.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_sector_features() {
+        let position = [-122.4, 47.3];
+        let sector = light(json!({"COLOUR": ["3"], "LITC": "LITRD", "SECTR1": 90.0, "SECTR2": 180.0, "VALNMR": 5.0}));
+        let features = sector_features(&position, &sector);
+        let kind = |kind: &str| features.iter()
+            .filter(|f| f.properties.as_ref().unwrap()["KIND"] == kind)
+            .collect::<Vec<_>>();
+        let zooms = SECTOR_ZOOMS.count();
+        assert_eq!(2 * zooms, kind("leg").len());
+        assert_eq!(zooms, kind("arc").len());
+        assert_eq!(1, kind("range").len());
+
+        // the first leg is drawn from the light away from seaward, 270 degrees
+        let leg = kind("leg")[2 * zooms - 2].geometry.as_ref().unwrap();
+        if let GeoValue::LineString(line) = &leg.value {
+            assert!(line[1][0] < position[0]);
+            assert!((line[1][1] - position[1]).abs() < 1e-3);
+        } else {
+            panic!("leg is not a line");
+        }
+        // arcs sweep clockwise from 270 to 360 degrees
+        if let GeoValue::LineString(line) = &kind("range")[0].geometry.as_ref().unwrap().value {
+            assert_eq!(46, line.len());
+            assert!(line.last().unwrap()[1] > position[1]);
+        }
+        assert_eq!(Some(json!({"minzoom": 6, "maxzoom": 6})), kind("arc")[0].foreign_members.as_ref().map(|m| m["tippecanoe"].clone()));
+
+        let all_round = light(json!({"SECTR1": 0.0, "SECTR2": 360.0}));
+        assert!(sector_features(&position, &all_round).iter().all(|f| f.properties.as_ref().unwrap()["KIND"] != "leg"));
+
+        let directional = light(json!({"CATLIT": ["1"], "ORIENT": 45.0}));
+        assert_eq!(zooms, sector_features(&position, &directional).len());
+        assert!(sector_features(&position, &light(json!({"COLOUR": ["1"]}))).is_empty());
+    }
+
+    #[test]
+    fn test_litdsn01() {
+        let description = |value: Value| litdsn01(&light(value), false).unwrap();
+        assert_eq!("Fl(2) R 6s 12m 5M", description(json!({
+            "LITCHR": 2, "SIGGRP": "(2)", "COLOUR": ["3"], "SIGPER": 6.0, "HEIGHT": 12.0, "VALNMR": 5.0
+        })));
+        assert_eq!("Oc(2+1) G 12s", description(json!({
+            "LITCHR": 8, "SIGGRP": "(2+1)", "COLOUR": ["4"], "SIGPER": 12.0
+        })));
+        assert_eq!("Iso W 2.5s 8.5m", description(json!({
+            "LITCHR": 7, "SIGGRP": "()", "COLOUR": ["1"], "SIGPER": 2.5, "HEIGHT": 8.5
+        })));
+        assert_eq!("Q G", description(json!({"LITCHR": 4, "SIGGRP": "()", "COLOUR": ["4"]})));
+        assert_eq!("VQ(3) W 5s", description(json!({"LITCHR": 5, "SIGGRP": "(3)", "COLOUR": ["1"], "SIGPER": 5.0})));
+        assert_eq!("Al.Fl WR 10s", description(json!({"LITCHR": 19, "SIGGRP": "(1)", "COLOUR": ["1", "3"], "SIGPER": 10.0})));
+        assert_eq!("Mo(U) W 15s", description(json!({"LITCHR": 12, "SIGGRP": "(U)", "COLOUR": ["1"], "SIGPER": 15.0})));
+        assert_eq!("F Y", description(json!({"LITCHR": ["1"], "COLOUR": ["6"]})));
+        assert_eq!(None, litdsn01(&light(json!({"COLOUR": ["1"]})), false));
+    }
+
+    #[test]
+    fn test_litdsn01_feet() {
+        let properties = light(json!({"LITCHR": 2, "SIGGRP": "(2)", "COLOUR": ["3"], "SIGPER": 6.0, "HEIGHT": 12.0, "VALNMR": 5.0}));
+        assert_eq!(Some(String::from("Fl(2) R 6s 39ft 5M")), litdsn01(&properties, true));
+        let properties = light(json!({"LITCHR": 2, "COLOUR": ["4"], "SIGPER": 4.0, "HEIGHT": 4.6}));
+        assert_eq!(Some(String::from("Fl G 4s 15ft")), litdsn01(&properties, true));
+        assert_eq!(Some(String::from("Fl G 4s 4.6m")), litdsn01(&properties, false));
+
+        let mut lit = light(json!({"LITCHR": 2, "COLOUR": ["4"], "SIGPER": 4.0, "HEIGHT": 4.6}));
+        lights06(&GeoValue::Point(vec![-122.4, 47.3]), &mut lit, &Context::default());
+        assert_eq!(json!("Fl G 4s 4.6m"), lit["LITDSN"]);
+        assert_eq!(json!("Fl G 4s 15ft"), lit["LITDSN_FT"]);
+    }
+}
@@ -0,0 +1,258 @@
+use std::include_bytes;
+use geojson::Value as GeoValue;
+use serde_json::{Value, Number, from_slice};
+use crate::geojson_builder::JsonObject;
+use crate::presentation::{self, Instruction};
+use crate::csp::{self, Context, MarinerSettings};
+use crate::s57::ChartLayer;
+
+/// S-52 presentation library look-up tables extracted from OpenCPN's chartsymbols.xml
+/// see reference_material/opencpn/chartsymbolextractor.py `write_lookups()`
+const LOOKUPS_JSON: &[u8] = include_bytes!("lookups.json");
+
+/// The point symbol table, `Paper` chart symbols or `Simplified` symbols
+const POINT_TABLE: &str = "Paper";
+/// The area table, `Symbolized` boundaries or `Plain` boundaries
+const AREA_TABLE: &str = "Symbolized";
+const LINE_TABLE: &str = "Lines";
+
+lazy_static! {
+    pub static ref LOOKUPS: Vec<Lookup> = {
+        let json: Value = from_slice(LOOKUPS_JSON).unwrap();
+        json.as_array().unwrap().iter().map(Lookup::from_json).collect()
+    };
+}
+
+/// A look-up table entry, the presentation of an object class with an attribute combination
+///
+/// <lookup id="345" RCID="32380" name="AIRARE">
+///     <type>Area</type>
+///     <disp-prio>Area 1</disp-prio>
+///     <table-name>Symbolized</table-name>
+///     <attrib-code index="0">CONVIS1</attrib-code>
+///     <instruction>AC(LANDA);AP(AIRARE02);LS(SOLD,1,CHBLK)</instruction>
+///     <display-cat>Standard</display-cat>
+/// </lookup>
+pub struct Lookup {
+    pub name: String,
+    pub geometry_type: String,
+    pub table: String,
+    pub display_priority: u8,
+    pub display_category: String,
+    /// attribute acronym and value eg ("COLOUR", "3,4,3"), an empty value matches any value and `?`
+    /// matches an attribute without a value
+    pub attributes: Vec<(String, String)>,
+    pub instruction: String,
+}
+
+impl Lookup {
+    fn from_json(json: &Value) -> Lookup {
+        let text = |key: &str| json[key].as_str().unwrap_or("").to_string();
+        let attributes = json["attributes"].as_array().map(|a| {
+            a.iter().filter_map(|ea| ea.as_str()).filter(|ea| ea.len() >= 6).map(|ea| {
+                (ea[0..6].to_string(), ea[6..].trim().to_string())
+            }).collect()
+        }).unwrap_or(vec![]);
+        Lookup {
+            name: text("name"),
+            geometry_type: text("type"),
+            table: text("table"),
+            display_priority: display_priority(json["disp_prio"].as_str().unwrap_or("")),
+            display_category: text("display_cat"),
+            attributes,
+            instruction: text("instruction"),
+        }
+    }
+
+    /// The number of matching attributes or None when any of the attributes does not match
+    fn matches(&self, properties: &JsonObject) -> Option<usize> {
+        for (acronym, expected) in &self.attributes {
+            let value = properties.get(acronym).and_then(attribute_value);
+            let matched = match (expected.as_str(), value) {
+                ("?", value) => value.is_none(),
+                ("", value) => value.is_some(),
+                (_, None) => false,
+                (expected, Some(value)) => attribute_equals(expected, &value),
+            };
+            if !matched {
+                return None;
+            }
+        }
+        Some(self.attributes.len())
+    }
+}
+
+/// S-52 display priority 0 - 9, drawing order of the presentation
+///     0	No data
+///     1	Group 1 (skin of the earth)
+///     2	Area 1
+///     3	Area 2
+///     4	Point Symbol
+///     5	Line Symbol
+///     6	Area Symbol
+///     7	Routing
+///     8	Hazards
+///     9	Mariners
+fn display_priority(name: &str) -> u8 {
+    match name {
+        "Group 1" => 1,
+        "Area 1" => 2,
+        "Area 2" => 3,
+        "Point Symbol" => 4,
+        "Line Symbol" => 5,
+        "Area Symbol" => 6,
+        "Routing" => 7,
+        "Hazards" => 8,
+        "Mariners" => 9,
+        _ => 0,
+    }
+}
+
+/// The attribute value as it is written in the look-up table, lists are comma separated
+fn attribute_value(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) if !s.is_empty() => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Array(a) if !a.is_empty() => Some(a.iter().map(|ea| {
+            ea.as_str().map(String::from).unwrap_or(ea.to_string())
+        }).collect::<Vec<String>>().join(",")),
+        _ => None,
+    }
+}
+
+fn attribute_equals(expected: &str, value: &str) -> bool {
+    match (expected.parse::<f64>(), value.parse::<f64>()) {
+        (Ok(e), Ok(v)) => (e - v).abs() < f64::EPSILON,
+        _ => expected == value,
+    }
+}
+
+fn geometry_type(geometry: &GeoValue) -> &'static str {
+    match geometry {
+        GeoValue::Point(_) | GeoValue::MultiPoint(_) => "Point",
+        GeoValue::LineString(_) | GeoValue::MultiLineString(_) => "Line",
+        _ => "Area",
+    }
+}
+
+/// Finds the best look-up table entry for the object class (S-52 PresLib 8.3.3.4). Entries of the
+/// class where every attribute matches are candidates and the one matching the most attributes
+/// wins, the first entry wins a tie. When nothing matches the entry without attributes is used.
+pub fn find_lookup(name: &str, geometry: &GeoValue, properties: &JsonObject) -> Option<&'static Lookup> {
+    let geometry_type = geometry_type(geometry);
+    let table = match geometry_type {
+        "Point" => POINT_TABLE,
+        "Line" => LINE_TABLE,
+        _ => AREA_TABLE,
+    };
+    let candidates: Vec<&'static Lookup> = LOOKUPS.iter()
+        .filter(|l| l.name == name && l.geometry_type == geometry_type && l.table == table)
+        .collect();
+    let mut best: Option<(usize, &'static Lookup)> = None;
+    for lookup in candidates.iter().filter(|l| !l.attributes.is_empty()) {
+        if let Some(count) = lookup.matches(properties) {
+            if best.map(|b| count > b.0).unwrap_or(true) {
+                best = Some((count, lookup));
+            }
+        }
+    }
+    best.map(|b| b.1)
+        .or_else(|| candidates.iter().find(|l| l.attributes.is_empty()).cloned())
+        .or_else(|| candidates.first().cloned())
+}
+
+/// The point symbol `SY` of the best look-up table entry
+pub fn symbol(name: &str, geometry: &GeoValue, properties: &JsonObject) -> Option<String> {
+    find_lookup(name, geometry, properties).and_then(|lookup| {
+        presentation::parse(&lookup.instruction).into_iter().find_map(|instruction| match instruction {
+            Instruction::Symbol { name, .. } => Some(name),
+            _ => None,
+        })
+    })
+}
+
+/// Attaches the S-52 presentation of the feature
+///     INST - instruction eg `AC(LANDA);AP(AIRARE02);LS(SOLD,1,CHBLK)`
+///     DPRI - display priority
+///     DCAT - display category Displaybase, Standard, Other or Mariners
+/// and the properties of the expanded instructions, see `presentation::expand`. Conditional
+/// symbology procedures `CS(...)` are replaced with the instructions they return.
+pub fn symbolize(name: &str, geometry: &GeoValue, properties: &mut JsonObject, context: &Context) {
+    if let Some(lookup) = find_lookup(name, geometry, properties) {
+        properties.insert(String::from("INST"), Value::String(lookup.instruction.clone()));
+        properties.insert(String::from("DPRI"), Value::Number(Number::from(lookup.display_priority)));
+        properties.insert(String::from("DCAT"), Value::String(lookup.display_category.clone()));
+        let mut instructions = vec![];
+        for instruction in presentation::parse(&lookup.instruction) {
+            match instruction {
+                Instruction::Procedure { name: procedure } => {
+                    let result = csp::procedure(&procedure, name, geometry, properties, context);
+                    instructions.push(Instruction::Procedure { name: procedure });
+                    instructions.extend(presentation::parse(&result));
+                }
+                instruction => instructions.push(instruction),
+            }
+        }
+        presentation::expand(&instructions, properties);
+    }
+}
+
+/// Symbolizes the features of a cell's layers. Procedures depend on neighbouring features eg the
+/// depth area an obstruction lies in, so every layer is converted before any is symbolized.
+pub fn symbolize_layers(layers: &mut [ChartLayer], settings: MarinerSettings) {
+    let mut context = Context::new(settings);
+    for layer in layers.iter() {
+        for feature in &layer.collection.features {
+            if let (Some(geometry), Some(properties)) = (&feature.geometry, &feature.properties) {
+                context.add(&layer.name, &geometry.value, properties);
+            }
+        }
+    }
+    for layer in layers.iter_mut() {
+        for feature in layer.collection.features.iter_mut() {
+            if let (Some(geometry), Some(properties)) = (&feature.geometry, &mut feature.properties) {
+                symbolize(&layer.name, &geometry.value, properties, &context);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    fn properties(value: Value) -> JsonObject {
+        value.as_object().unwrap().clone()
+    }
+
+    #[test]
+    fn test_find_lookup() {
+        let point = GeoValue::Point(vec![-122.4, 47.3]);
+        let cardinal = find_lookup("BOYCAR", &point, &properties(json!({"BOYSHP": 4, "CATCAM": 2}))).unwrap();
+        assert!(cardinal.instruction.starts_with("SY(BOYPIL70)"));
+        assert_eq!(8, cardinal.display_priority);
+        let shape = find_lookup("BOYCAR", &point, &properties(json!({"BOYSHP": 4, "CATCAM": 9}))).unwrap();
+        assert!(shape.instruction.starts_with("SY(BOYPIL01)"));
+        let default = find_lookup("BOYCAR", &point, &properties(json!({}))).unwrap();
+        assert!(default.instruction.starts_with("SY(BOYGEN03)"));
+
+        let lateral = find_lookup("BOYLAT", &point, &properties(json!({
+            "BOYSHP": 2, "COLOUR": ["3", "4", "3"]
+        }))).unwrap();
+        assert!(lateral.instruction.starts_with("SY(BOYCAN72)"));
+    }
+
+    #[test]
+    fn test_symbolize_area() {
+        let area = GeoValue::Polygon(vec![vec![vec![0.0, 0.0], vec![1.0, 0.0], vec![1.0, 1.0], vec![0.0, 0.0]]]);
+        let mut props = properties(json!({"DRVAL1": 3.0, "DRVAL2": 9.0}));
+        symbolize("DEPARE", &area, &mut props, &Context::default());
+        assert_eq!(json!("CS(DEPARE01)"), props["INST"]);
+        assert_eq!(json!("DEPARE01"), props["CS"]);
+        assert_eq!(json!("DEPMD"), props["AC"]);
+        let mut props = properties(json!({}));
+        symbolize("DEPARE", &area, &mut props, &Context::default());
+        assert!(props["INST"].as_str().unwrap().starts_with("AC(NODTA)"));
+    }
+}
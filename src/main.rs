@@ -8,20 +8,52 @@ mod styler;
 mod utils;
 mod colors;
 mod soundg;
-mod seaare;
 mod depare;
 mod depcnt;
 mod boyspp;
+mod boylat;
+mod boycar;
+mod boyisd;
+mod boysaw;
+mod boyinb;
+mod bcnspp;
+mod bcnlat;
+mod bcncar;
+mod bcnisd;
+mod bcnsaw;
+mod resare;
+mod tsslpt;
+mod bridge;
+mod cblsub;
+mod units;
+mod dates;
+mod sprites;
+mod topmar;
+mod relations;
 mod lights;
 mod catspm;
 mod util;
+mod mvt;
+mod tiler;
+mod mbtiles;
+mod pmtiles;
+mod iso8211;
+mod catalog;
+mod compositor;
+mod lookups;
+mod presentation;
+mod csp;
+#[cfg(test)]
+mod test_util;
 
 use std::path::Path;
+use catalog::Catalog;
+use s57::ChartLayer;
+use csp::MarinerSettings;
 
 extern crate clap;
 
 use clap::{Arg, App, SubCommand, ArgMatches};
-use std::fs;
 
 
 fn main() {
@@ -30,9 +62,9 @@ fn main() {
         .author("William Kamp <manimaul@gmail.com>")
         .about("Utility for generating S57 Mapbox vector tiles and styles")
         .subcommand(SubCommand::with_name("mbtiles")
-            .about("Renders S57 marine chart files into geojson and/or Mapbox MBTiles")
+            .about("Renders S57 marine chart files into geojson and/or Mapbox MBTiles or PMTiles")
             .arg(Arg::with_name("in_file")
-                .help("Sets the input S57 file which is usually ending in .000")
+                .help("Sets the input S57 file which is usually ending in .000 or an exchange set CATALOG.031")
                 .short("i")
                 .long("input")
                 .required(true)
@@ -65,6 +97,40 @@ fn main() {
                 .required(false)
                 .takes_value(true)
             )
+            .arg(Arg::with_name("min_zoom")
+                .help("Sets the minimum zoom level of the rendered tiles")
+                .long("min-zoom")
+                .required(false)
+                .takes_value(true)
+                .default_value("0")
+            )
+            .arg(Arg::with_name("max_zoom")
+                .help("Sets the maximum zoom level of the rendered tiles")
+                .long("max-zoom")
+                .required(false)
+                .takes_value(true)
+                .default_value("16")
+            )
+            .arg(Arg::with_name("format")
+                .help("Sets the tile archive format, chart.mbtiles or chart.pmtiles is written to the output directory")
+                .short("f")
+                .long("format")
+                .required(false)
+                .takes_value(true)
+                .possible_values(&["mbtiles", "pmtiles"])
+                .default_value("mbtiles")
+            )
+            .arg(Arg::with_name("relations")
+                .help("Write the feature relationships PARENT_LNAM and CHILDREN to the feature properties")
+                .long("relations")
+                .required(false)
+            )
+            .arg(Arg::with_name("date")
+                .help("Renders the chart as of the date YYYY-MM-DD leaving out features whose DATSTA/DATEND or PERSTA/PEREND exclude it")
+                .long("date")
+                .required(false)
+                .takes_value(true)
+            )
         )
         .subcommand(SubCommand::with_name("style")
             .about("Generates a Mapbox Vector style for S57 marine charts")
@@ -90,6 +156,40 @@ fn main() {
                 .required(true)
                 .takes_value(true)
             )
+            .arg(Arg::with_name("safety_contour")
+                .help("Sets the safety contour in meters, the shallowest contour at or deeper than it is emphasized")
+                .long("safety-contour")
+                .required(false)
+                .takes_value(true)
+                .default_value("3.0")
+            )
+            .arg(Arg::with_name("safety_depth")
+                .help("Sets the safety depth in meters, soundings at or shallower than it are emphasized")
+                .long("safety-depth")
+                .required(false)
+                .takes_value(true)
+                .default_value("3.0")
+            )
+            .arg(Arg::with_name("shallow_contour")
+                .help("Sets the shallow contour in meters used by the four depth shades")
+                .long("shallow-contour")
+                .required(false)
+                .takes_value(true)
+                .default_value("2.0")
+            )
+            .arg(Arg::with_name("deep_contour")
+                .help("Sets the deep contour in meters used by the four depth shades")
+                .long("deep-contour")
+                .required(false)
+                .takes_value(true)
+                .default_value("9.0")
+            )
+            .arg(Arg::with_name("two_shades")
+                .help("Shades depth areas shallow or deep of the safety contour instead of four shades")
+                .long("two-shades")
+                .required(false)
+                .takes_value(false)
+            )
         )
         .subcommand(SubCommand::with_name("config")
             .about("Generates a TileServer-GL Config")
@@ -130,18 +230,95 @@ fn mbtiles(matches: &ArgMatches) {
     let layer_ex = matches.value_of("layer_ex").map(|ex| ex.split(",").collect::<Vec<&str>>());
     let layer_in = matches.value_of("layer_in").map(|ex| ex.split(",").collect::<Vec<&str>>());
     let keep_geojson = matches.is_present("keep_geojson");
-    let s57 = s57::S57::open(Path::new(in_file)).unwrap();
-    let files = s57.render_geojson(
-        Path::new(out_dir),
-        false,
-        layer_ex,
-        layer_in,
-    );
-    s57::S57::generate_mbtiles(Path::new(out_dir), &files);
-    if !keep_geojson {
-        files.iter()
-            .map(|f| Path::new(f))
-            .for_each(|f| fs::remove_file(f).expect("expected to remove file"));
+    let relations = matches.is_present("relations");
+    let options = zoom_range(matches).and_then(|zoom| as_of_date(matches).map(|date| (zoom, date)));
+    let ((min_zoom, max_zoom), as_of) = match options {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}", message);
+            std::process::exit(1);
+        }
+    };
+    let (name, layers) = read_layers(Path::new(in_file), layer_ex, layer_in, relations, as_of, min_zoom, max_zoom);
+    let name = name.as_str();
+    if keep_geojson {
+        s57::S57::render_geojson(Path::new(out_dir), false, &layers);
+    }
+    match matches.value_of("format") {
+        Some("pmtiles") => s57::S57::generate_pmtiles(Path::new(out_dir), name, &layers, min_zoom, max_zoom),
+        _ => s57::S57::generate_mbtiles(Path::new(out_dir), name, &layers, min_zoom, max_zoom),
+    }
+}
+
+/// The minimum and maximum zoom of the tiles, at most `tiler::MAX_ZOOM` and in order
+fn zoom_range(matches: &ArgMatches) -> Result<(u8, u8), String> {
+    let zoom = |name: &str| -> Result<u8, String> {
+        let value = matches.value_of(name).unwrap();
+        match value.parse::<u8>() {
+            Ok(zoom) if zoom <= tiler::MAX_ZOOM => Ok(zoom),
+            _ => Err(format!("invalid {} {}, zoom levels are 0 to {}", name.replace('_', " "), value, tiler::MAX_ZOOM)),
+        }
+    };
+    let (min_zoom, max_zoom) = (zoom("min_zoom")?, zoom("max_zoom")?);
+    if min_zoom > max_zoom {
+        return Err(format!("the min zoom {} is greater than the max zoom {}", min_zoom, max_zoom));
+    }
+    Ok((min_zoom, max_zoom))
+}
+
+/// The date CCYYMMDD the chart is rendered as of
+fn as_of_date(matches: &ArgMatches) -> Result<Option<u32>, String> {
+    match matches.value_of("date") {
+        Some(date) => dates::parse_date(date)
+            .map(Some)
+            .ok_or_else(|| format!("invalid date {}, dates are YYYY-MM-DD", date)),
+        None => Ok(None),
+    }
+}
+
+/// Reads a single cell or every base cell (with its updates applied) listed in an exchange set
+/// catalogue. The cells of an exchange set are composited by navigational purpose. Returns the
+/// tileset name and layers.
+fn read_layers(
+    in_path: &Path,
+    layer_ex: Option<Vec<&str>>,
+    layer_in: Option<Vec<&str>>,
+    relations: bool,
+    as_of: Option<u32>,
+    min_zoom: u8,
+    max_zoom: u8,
+) -> (String, Vec<ChartLayer>) {
+    if Catalog::is_catalog(in_path) {
+        let catalog = Catalog::open(in_path).expect("could not read exchange set catalogue");
+        let cells = catalog.cells();
+        println!("exchange set {} contains {} cells", catalog.name(), cells.len());
+        let cells = cells.iter().filter_map(|cell| {
+            println!("reading cell {} with {} updates", cell.name(), cell.updates.len());
+            cell.updates.iter()
+                .filter(|u| !u.exists())
+                .for_each(|u| println!("missing update file: {:?}", u));
+            match s57::S57::open(&cell.path) {
+                Some(s57) => {
+                    let (intended_usage, scale) = s57.dataset_parameters();
+                    Some(compositor::Cell {
+                        name: cell.name(),
+                        intended_usage,
+                        scale,
+                        coverage: s57.coverage(),
+                        layers: s57.chart_layers(layer_ex.clone(), layer_in.clone(), relations, as_of),
+                    })
+                }
+                None => {
+                    println!("could not open cell: {:?}", cell.path);
+                    None
+                }
+            }
+        }).collect();
+        (catalog.name(), s57::merge_layers(compositor::composite(cells, min_zoom, max_zoom)))
+    } else {
+        let s57 = s57::S57::open(in_path).unwrap();
+        let name = in_path.file_stem().and_then(|s| s.to_str()).unwrap_or("chart").to_string();
+        (name, s57.chart_layers(layer_ex, layer_in, relations, as_of))
     }
 }
 
@@ -153,7 +330,18 @@ fn style(matches: &ArgMatches) {
     } else {
         format!("http://{}", socket_address)
     };
-    styler::create_style(Path::new(out_dir), &addess);
+    let depth = |name: &str| -> f64 {
+        matches.value_of(name).unwrap().parse().unwrap_or_else(|_| panic!("invalid {}", name))
+    };
+    let settings = MarinerSettings {
+        safety_contour: depth("safety_contour"),
+        safety_depth: depth("safety_depth"),
+        shallow_contour: depth("shallow_contour"),
+        deep_contour: depth("deep_contour"),
+        two_shades: matches.is_present("two_shades"),
+        ..Default::default()
+    };
+    styler::create_style(Path::new(out_dir), &addess, &settings);
 }
 
 
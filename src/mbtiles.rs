@@ -0,0 +1,106 @@
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use rusqlite::{Connection, params};
+use crate::tiler::TileMetadata;
+
+/// MBTiles 1.3 tile archive writer
+/// https://github.com/mapbox/mbtiles-spec/blob/master/1.3/spec.md
+pub struct MbTiles {
+    connection: Connection,
+}
+
+impl MbTiles {
+    pub fn create(path: &Path) -> MbTiles {
+        if path.exists() {
+            fs::remove_file(path).expect("could not remove existing mbtiles file");
+        };
+        let connection = Connection::open(path).expect("could not create mbtiles file");
+        connection.execute_batch("
+            CREATE TABLE metadata (name text, value text);
+            CREATE UNIQUE INDEX name ON metadata (name);
+            CREATE TABLE tiles (zoom_level integer, tile_column integer, tile_row integer, tile_data blob);
+            CREATE UNIQUE INDEX tile_index ON tiles (zoom_level, tile_column, tile_row);
+            BEGIN TRANSACTION;
+        ").expect("could not create mbtiles schema");
+        MbTiles { connection }
+    }
+
+    /// Inserts a tile addressed in the xyz scheme, the tile data is gzip compressed and the row is
+    /// flipped into the tms scheme used by mbtiles.
+    pub fn insert_tile(&self, z: u8, x: u32, y: u32, data: &[u8]) {
+        let tms_y = (1_u32 << z) - 1 - y;
+        self.connection.execute(
+            "INSERT INTO tiles (zoom_level, tile_column, tile_row, tile_data) VALUES (?1, ?2, ?3, ?4)",
+            params![z as u32, x, tms_y, gzip(data)],
+        ).expect("could not insert tile");
+    }
+
+    pub fn finish(self, metadata: &TileMetadata) {
+        let (lng, lat, zoom) = metadata.center();
+        let bounds = metadata.bounds;
+        let json = serde_json::json!({ "vector_layers": metadata.vector_layers });
+        let rows = vec![
+            ("name", metadata.name.clone()),
+            ("format", String::from("pbf")),
+            ("type", String::from("overlay")),
+            ("version", String::from("1")),
+            ("description", format!("{} S57 marine chart", metadata.name)),
+            ("bounds", format!("{},{},{},{}", bounds[0], bounds[1], bounds[2], bounds[3])),
+            ("center", format!("{},{},{}", lng, lat, zoom)),
+            ("minzoom", metadata.min_zoom.to_string()),
+            ("maxzoom", metadata.max_zoom.to_string()),
+            ("json", json.to_string()),
+        ];
+        for (name, value) in rows {
+            self.connection.execute(
+                "INSERT INTO metadata (name, value) VALUES (?1, ?2)",
+                params![name, value],
+            ).expect("could not insert metadata");
+        }
+        self.connection.execute_batch("COMMIT;").expect("could not commit mbtiles");
+    }
+}
+
+pub fn gzip(data: &[u8]) -> Vec<u8> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data).expect("could not compress tile");
+    encoder.finish().expect("could not compress tile")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_mbtiles() {
+        let path = std::env::temp_dir().join("s57tiler_test.mbtiles");
+        let mb_tiles = MbTiles::create(&path);
+        mb_tiles.insert_tile(1, 0, 0, &[1, 2, 3]);
+        mb_tiles.finish(&TileMetadata {
+            name: String::from("US5WA22M"),
+            min_zoom: 0,
+            max_zoom: 1,
+            bounds: [-122.5, 47.2, -122.3, 47.3],
+            vector_layers: json!([]),
+        });
+
+        let connection = Connection::open(&path).unwrap();
+        let row: u32 = connection.query_row(
+            "SELECT tile_row FROM tiles WHERE zoom_level = 1 AND tile_column = 0", params![], |r| r.get(0),
+        ).unwrap();
+        assert_eq!(1, row);
+        let format: String = connection.query_row(
+            "SELECT value FROM metadata WHERE name = 'format'", params![], |r| r.get(0),
+        ).unwrap();
+        assert_eq!("pbf", format);
+        let bounds: String = connection.query_row(
+            "SELECT value FROM metadata WHERE name = 'bounds'", params![], |r| r.get(0),
+        ).unwrap();
+        assert_eq!("-122.5,47.2,-122.3,47.3", bounds);
+        fs::remove_file(&path).ok();
+    }
+}
@@ -0,0 +1,351 @@
+use std::collections::HashMap;
+use serde_json::Value;
+use crate::geojson_builder::JsonObject;
+
+/// Mapbox Vector Tile encoder
+/// https://github.com/mapbox/vector-tile-spec/tree/master/2.1
+///
+/// vector_tile.proto field numbers
+///     Tile:    layers = 3
+///     Layer:   version = 15, name = 1, features = 2, keys = 3, values = 4, extent = 5
+///     Feature: id = 1, tags = 2 (packed), type = 3, geometry = 4 (packed)
+///     Value:   string = 1, float = 2, double = 3, int = 4, uint = 5, sint = 6, bool = 7
+pub const EXTENT: u32 = 4096;
+
+const WIRE_VARINT: u32 = 0;
+const WIRE_64: u32 = 1;
+const WIRE_LEN: u32 = 2;
+
+const CMD_MOVE_TO: u32 = 1;
+const CMD_LINE_TO: u32 = 2;
+const CMD_CLOSE_PATH: u32 = 7;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum GeomType {
+    Point = 1,
+    LineString = 2,
+    Polygon = 3,
+}
+
+enum TileValue {
+    String(String),
+    Double(f64),
+    Uint(u64),
+    Sint(i64),
+    Bool(bool),
+}
+
+impl TileValue {
+    fn from_json(value: &Value) -> Option<TileValue> {
+        match value {
+            Value::Null => None,
+            Value::Bool(b) => Some(TileValue::Bool(*b)),
+            Value::String(s) => Some(TileValue::String(s.clone())),
+            Value::Number(n) => {
+                if let Some(u) = n.as_u64() {
+                    Some(TileValue::Uint(u))
+                } else if let Some(i) = n.as_i64() {
+                    Some(TileValue::Sint(i))
+                } else {
+                    n.as_f64().map(TileValue::Double)
+                }
+            }
+            // tippecanoe also flattens lists and objects into their json string representation
+            Value::Array(_) | Value::Object(_) => Some(TileValue::String(value.to_string())),
+        }
+    }
+
+    /// A key which uniquely identifies this value within a layer's value table
+    fn index_key(&self) -> String {
+        match self {
+            TileValue::String(s) => format!("s:{}", s),
+            TileValue::Double(d) => format!("d:{}", d.to_bits()),
+            TileValue::Uint(u) => format!("u:{}", u),
+            TileValue::Sint(i) => format!("i:{}", i),
+            TileValue::Bool(b) => format!("b:{}", b),
+        }
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut buf = vec![];
+        match self {
+            TileValue::String(s) => write_bytes(&mut buf, 1, s.as_bytes()),
+            TileValue::Double(d) => {
+                write_key(&mut buf, 3, WIRE_64);
+                buf.extend_from_slice(&d.to_le_bytes());
+            }
+            TileValue::Uint(u) => {
+                write_key(&mut buf, 5, WIRE_VARINT);
+                write_varint(&mut buf, *u);
+            }
+            TileValue::Sint(i) => {
+                write_key(&mut buf, 6, WIRE_VARINT);
+                write_varint(&mut buf, zigzag64(*i));
+            }
+            TileValue::Bool(b) => {
+                write_key(&mut buf, 7, WIRE_VARINT);
+                write_varint(&mut buf, if *b { 1 } else { 0 });
+            }
+        }
+        buf
+    }
+}
+
+struct TileFeature {
+    tags: Vec<u32>,
+    geom_type: GeomType,
+    geometry: Vec<u32>,
+}
+
+pub struct Layer {
+    name: String,
+    keys: Vec<String>,
+    key_index: HashMap<String, u32>,
+    values: Vec<TileValue>,
+    value_index: HashMap<String, u32>,
+    features: Vec<TileFeature>,
+}
+
+impl Layer {
+    pub fn new(name: &str) -> Layer {
+        Layer {
+            name: String::from(name),
+            keys: vec![],
+            key_index: HashMap::new(),
+            values: vec![],
+            value_index: HashMap::new(),
+            features: vec![],
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.features.is_empty()
+    }
+
+    pub fn add_feature(&mut self, geom_type: GeomType, geometry: Vec<u32>, properties: &JsonObject) {
+        if geometry.is_empty() {
+            return;
+        }
+        let mut tags = vec![];
+        for (key, value) in properties {
+            if let Some(tile_value) = TileValue::from_json(value) {
+                tags.push(self.key_tag(key));
+                tags.push(self.value_tag(tile_value));
+            }
+        }
+        self.features.push(TileFeature { tags, geom_type, geometry });
+    }
+
+    fn key_tag(&mut self, key: &String) -> u32 {
+        if let Some(idx) = self.key_index.get(key) {
+            return *idx;
+        }
+        let idx = self.keys.len() as u32;
+        self.keys.push(key.clone());
+        self.key_index.insert(key.clone(), idx);
+        idx
+    }
+
+    fn value_tag(&mut self, value: TileValue) -> u32 {
+        let index_key = value.index_key();
+        if let Some(idx) = self.value_index.get(&index_key) {
+            return *idx;
+        }
+        let idx = self.values.len() as u32;
+        self.values.push(value);
+        self.value_index.insert(index_key, idx);
+        idx
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut buf = vec![];
+        write_key(&mut buf, 15, WIRE_VARINT);
+        write_varint(&mut buf, 2);
+        write_bytes(&mut buf, 1, self.name.as_bytes());
+        for feature in &self.features {
+            let mut fbuf = vec![];
+            write_packed(&mut fbuf, 2, &feature.tags);
+            write_key(&mut fbuf, 3, WIRE_VARINT);
+            write_varint(&mut fbuf, feature.geom_type as u64);
+            write_packed(&mut fbuf, 4, &feature.geometry);
+            write_bytes(&mut buf, 2, &fbuf);
+        }
+        for key in &self.keys {
+            write_bytes(&mut buf, 3, key.as_bytes());
+        }
+        for value in &self.values {
+            write_bytes(&mut buf, 4, &value.encode());
+        }
+        write_key(&mut buf, 5, WIRE_VARINT);
+        write_varint(&mut buf, EXTENT as u64);
+        buf
+    }
+}
+
+pub struct Tile {
+    layers: Vec<Layer>,
+}
+
+impl Tile {
+    pub fn new() -> Tile {
+        Tile { layers: vec![] }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.layers.iter().all(|l| l.is_empty())
+    }
+
+    pub fn layer(&mut self, name: &str) -> &mut Layer {
+        let idx = match self.layers.iter().position(|l| l.name == name) {
+            Some(idx) => idx,
+            None => {
+                self.layers.push(Layer::new(name));
+                self.layers.len() - 1
+            }
+        };
+        &mut self.layers[idx]
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buf = vec![];
+        for layer in self.layers.iter().filter(|l| !l.is_empty()) {
+            write_bytes(&mut buf, 3, &layer.encode());
+        }
+        buf
+    }
+}
+
+/// Encodes a set of tile space points as a single MoveTo command
+pub fn encode_points(points: &[(i32, i32)]) -> Vec<u32> {
+    let mut geometry = vec![];
+    if points.is_empty() {
+        return geometry;
+    }
+    let mut cursor = (0, 0);
+    geometry.push(command(CMD_MOVE_TO, points.len()));
+    for p in points {
+        push_delta(&mut geometry, &mut cursor, p);
+    }
+    geometry
+}
+
+/// Encodes tile space line strings, each line must have at least 2 points
+pub fn encode_lines(lines: &[Vec<(i32, i32)>]) -> Vec<u32> {
+    let mut geometry = vec![];
+    let mut cursor = (0, 0);
+    for line in lines.iter().filter(|l| l.len() >= 2) {
+        geometry.push(command(CMD_MOVE_TO, 1));
+        push_delta(&mut geometry, &mut cursor, &line[0]);
+        geometry.push(command(CMD_LINE_TO, line.len() - 1));
+        for p in &line[1..] {
+            push_delta(&mut geometry, &mut cursor, p);
+        }
+    }
+    geometry
+}
+
+/// Encodes tile space polygon rings, rings are open (the first point is not repeated) and must
+/// already be wound per the spec: exterior rings have a positive area and interior rings negative.
+pub fn encode_rings(rings: &[Vec<(i32, i32)>]) -> Vec<u32> {
+    let mut geometry = vec![];
+    let mut cursor = (0, 0);
+    for ring in rings.iter().filter(|r| r.len() >= 3) {
+        geometry.push(command(CMD_MOVE_TO, 1));
+        push_delta(&mut geometry, &mut cursor, &ring[0]);
+        geometry.push(command(CMD_LINE_TO, ring.len() - 1));
+        for p in &ring[1..] {
+            push_delta(&mut geometry, &mut cursor, p);
+        }
+        geometry.push(command(CMD_CLOSE_PATH, 1));
+    }
+    geometry
+}
+
+fn command(id: u32, count: usize) -> u32 {
+    (id & 0x7) | ((count as u32) << 3)
+}
+
+fn push_delta(geometry: &mut Vec<u32>, cursor: &mut (i32, i32), p: &(i32, i32)) {
+    geometry.push(zigzag32(p.0 - cursor.0));
+    geometry.push(zigzag32(p.1 - cursor.1));
+    *cursor = *p;
+}
+
+fn zigzag32(n: i32) -> u32 {
+    ((n << 1) ^ (n >> 31)) as u32
+}
+
+fn zigzag64(n: i64) -> u64 {
+    ((n << 1) ^ (n >> 63)) as u64
+}
+
+pub fn write_varint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+fn write_key(buf: &mut Vec<u8>, field: u32, wire_type: u32) {
+    write_varint(buf, ((field << 3) | wire_type) as u64);
+}
+
+fn write_bytes(buf: &mut Vec<u8>, field: u32, bytes: &[u8]) {
+    write_key(buf, field, WIRE_LEN);
+    write_varint(buf, bytes.len() as u64);
+    buf.extend_from_slice(bytes);
+}
+
+fn write_packed(buf: &mut Vec<u8>, field: u32, values: &[u32]) {
+    let mut packed = vec![];
+    values.iter().for_each(|v| write_varint(&mut packed, *v as u64));
+    write_bytes(buf, field, &packed);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_zigzag() {
+        assert_eq!(0, zigzag32(0));
+        assert_eq!(1, zigzag32(-1));
+        assert_eq!(2, zigzag32(1));
+        assert_eq!(3, zigzag32(-2));
+        assert_eq!(4294967295, zigzag32(i32::MIN));
+    }
+
+    #[test]
+    fn test_varint() {
+        let mut buf = vec![];
+        write_varint(&mut buf, 300);
+        assert_eq!(vec![0xac, 0x02], buf);
+    }
+
+    #[test]
+    fn test_geometry_encoding() {
+        // examples from the vector tile specification 4.3.5
+        assert_eq!(vec![9, 50, 34], encode_points(&[(25, 17)]));
+        assert_eq!(vec![17, 10, 14, 3, 9], encode_points(&[(5, 7), (3, 2)]));
+        assert_eq!(
+            vec![9, 4, 4, 18, 0, 16, 16, 0, 9, 17, 17, 10, 4, 8],
+            encode_lines(&[vec![(2, 2), (2, 10), (10, 10)], vec![(1, 1), (3, 5)]])
+        );
+        assert_eq!(vec![9, 6, 12, 18, 10, 12, 24, 44, 15], encode_rings(&[vec![(3, 6), (8, 12), (20, 34)]]));
+    }
+
+    #[test]
+    fn test_layer_tables() {
+        let mut tile = Tile::new();
+        let props = json!({"SY": "BOYCAN60", "METERS": 3}).as_object().unwrap().clone();
+        tile.layer("BOYSPP").add_feature(GeomType::Point, encode_points(&[(25, 17)]), &props);
+        tile.layer("BOYSPP").add_feature(GeomType::Point, encode_points(&[(5, 7)]), &props);
+        let layer = tile.layer("BOYSPP");
+        assert_eq!(2, layer.keys.len());
+        assert_eq!(2, layer.values.len());
+        assert_eq!(layer.features[0].tags, layer.features[1].tags);
+        assert!(!tile.encode().is_empty());
+    }
+}
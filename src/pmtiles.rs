@@ -0,0 +1,242 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde_json::json;
+use crate::mbtiles::gzip;
+use crate::mvt::write_varint;
+use crate::tiler::TileMetadata;
+
+/// PMTiles version 3 single file tile archive writer
+/// https://github.com/protomaps/PMTiles/blob/main/spec/v3/spec.md
+///
+/// Layout: header (127 bytes) | root directory | json metadata | leaf directories | tile data
+const HEADER_LEN: usize = 127;
+/// The header and root directory must fit in the first 16 KiB so clients can fetch both at once
+const ROOT_MAX_LEN: usize = 16_384 - HEADER_LEN;
+const COMPRESSION_GZIP: u8 = 2;
+const TILE_TYPE_MVT: u8 = 1;
+
+struct Entry {
+    tile_id: u64,
+    offset: u64,
+    length: u32,
+    run_length: u32,
+}
+
+pub struct PmTiles {
+    path: PathBuf,
+    tiles: Vec<(u64, Vec<u8>)>,
+}
+
+impl PmTiles {
+    pub fn create(path: &Path) -> PmTiles {
+        if path.exists() {
+            fs::remove_file(path).expect("could not remove existing pmtiles file");
+        };
+        PmTiles {
+            path: path.to_path_buf(),
+            tiles: vec![],
+        }
+    }
+
+    /// Adds a tile addressed in the xyz scheme, the tile data is gzip compressed
+    pub fn insert_tile(&mut self, z: u8, x: u32, y: u32, data: &[u8]) {
+        self.tiles.push((zxy_to_tile_id(z, x, y), gzip(data)));
+    }
+
+    pub fn finish(mut self, metadata: &TileMetadata) {
+        // tile data is clustered, ordered by tile id, and identical tiles are stored once
+        self.tiles.sort_by_key(|t| t.0);
+        let mut tile_data: Vec<u8> = vec![];
+        let mut contents: HashMap<Vec<u8>, (u64, u32)> = HashMap::new();
+        let mut entries: Vec<Entry> = vec![];
+        let addressed_tiles = self.tiles.len() as u64;
+        for (tile_id, data) in self.tiles {
+            let (offset, length) = match contents.get(&data) {
+                Some(location) => *location,
+                None => {
+                    let location = (tile_data.len() as u64, data.len() as u32);
+                    tile_data.extend_from_slice(&data);
+                    contents.insert(data, location);
+                    location
+                }
+            };
+            match entries.last_mut() {
+                Some(last) if last.offset == offset && last.tile_id + last.run_length as u64 == tile_id => {
+                    last.run_length += 1;
+                }
+                _ => entries.push(Entry { tile_id, offset, length, run_length: 1 }),
+            }
+        }
+
+        let (root, leaves) = build_directories(&entries);
+        let (lng, lat, zoom) = metadata.center();
+        let json_metadata = gzip(json!({
+            "name": metadata.name,
+            "format": "pbf",
+            "type": "overlay",
+            "description": format!("{} S57 marine chart", metadata.name),
+            "vector_layers": metadata.vector_layers
+        }).to_string().as_bytes());
+
+        let root_offset = HEADER_LEN as u64;
+        let metadata_offset = root_offset + root.len() as u64;
+        let leaves_offset = metadata_offset + json_metadata.len() as u64;
+        let data_offset = leaves_offset + leaves.len() as u64;
+
+        let mut header: Vec<u8> = Vec::with_capacity(HEADER_LEN);
+        header.extend_from_slice(b"PMTiles");
+        header.push(3);
+        for value in [
+            root_offset, root.len() as u64,
+            metadata_offset, json_metadata.len() as u64,
+            leaves_offset, leaves.len() as u64,
+            data_offset, tile_data.len() as u64,
+            addressed_tiles, entries.len() as u64, contents.len() as u64,
+        ].iter() {
+            header.extend_from_slice(&value.to_le_bytes());
+        }
+        header.push(1); // clustered
+        header.push(COMPRESSION_GZIP); // internal compression
+        header.push(COMPRESSION_GZIP); // tile compression
+        header.push(TILE_TYPE_MVT);
+        header.push(metadata.min_zoom);
+        header.push(metadata.max_zoom);
+        for value in [metadata.bounds[0], metadata.bounds[1], metadata.bounds[2], metadata.bounds[3]].iter() {
+            header.extend_from_slice(&e7(*value).to_le_bytes());
+        }
+        header.push(zoom);
+        header.extend_from_slice(&e7(lng).to_le_bytes());
+        header.extend_from_slice(&e7(lat).to_le_bytes());
+
+        let mut archive = header;
+        archive.extend_from_slice(&root);
+        archive.extend_from_slice(&json_metadata);
+        archive.extend_from_slice(&leaves);
+        archive.extend_from_slice(&tile_data);
+        fs::write(&self.path, archive).expect("could not write pmtiles file");
+    }
+}
+
+fn e7(degrees: f64) -> i32 {
+    (degrees * 10_000_000.0).round() as i32
+}
+
+/// Tile ids are the position along a hilbert curve, counting all the tiles of the lower zoom levels
+pub fn zxy_to_tile_id(z: u8, x: u32, y: u32) -> u64 {
+    let mut acc = 0_u64;
+    for tz in 0..z {
+        acc += 1_u64 << (2 * tz as u64);
+    }
+    let n = 1_u64 << z;
+    let (mut tx, mut ty) = (x as u64, y as u64);
+    let mut d = 0_u64;
+    let mut s = n / 2;
+    while s > 0 {
+        let rx = if tx & s > 0 { 1 } else { 0 };
+        let ry = if ty & s > 0 { 1 } else { 0 };
+        d += s * s * ((3 * rx) ^ ry);
+        if ry == 0 {
+            if rx == 1 {
+                tx = n - 1 - tx;
+                ty = n - 1 - ty;
+            }
+            std::mem::swap(&mut tx, &mut ty);
+        }
+        s /= 2;
+    }
+    acc + d
+}
+
+fn serialize_directory(entries: &[Entry]) -> Vec<u8> {
+    let mut buf = vec![];
+    write_varint(&mut buf, entries.len() as u64);
+    let mut last_id = 0;
+    for entry in entries {
+        write_varint(&mut buf, entry.tile_id - last_id);
+        last_id = entry.tile_id;
+    }
+    entries.iter().for_each(|e| write_varint(&mut buf, e.run_length as u64));
+    entries.iter().for_each(|e| write_varint(&mut buf, e.length as u64));
+    for (i, entry) in entries.iter().enumerate() {
+        let contiguous = i > 0 && entry.offset == entries[i - 1].offset + entries[i - 1].length as u64;
+        write_varint(&mut buf, if contiguous { 0 } else { entry.offset + 1 });
+    }
+    gzip(&buf)
+}
+
+/// Returns the compressed root directory and leaf directories. When every entry does not fit in
+/// the root, entries are split into leaf directories which the root points to (run length 0).
+fn build_directories(entries: &[Entry]) -> (Vec<u8>, Vec<u8>) {
+    let root = serialize_directory(entries);
+    if root.len() <= ROOT_MAX_LEN {
+        return (root, vec![]);
+    }
+    let mut leaf_size = 4096;
+    loop {
+        let mut leaves: Vec<u8> = vec![];
+        let mut root_entries: Vec<Entry> = vec![];
+        for chunk in entries.chunks(leaf_size) {
+            let leaf = serialize_directory(chunk);
+            root_entries.push(Entry {
+                tile_id: chunk[0].tile_id,
+                offset: leaves.len() as u64,
+                length: leaf.len() as u32,
+                run_length: 0,
+            });
+            leaves.extend_from_slice(&leaf);
+        }
+        let root = serialize_directory(&root_entries);
+        if root.len() <= ROOT_MAX_LEN {
+            return (root, leaves);
+        }
+        leaf_size *= 2;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_tile_id() {
+        assert_eq!(0, zxy_to_tile_id(0, 0, 0));
+        assert_eq!(1, zxy_to_tile_id(1, 0, 0));
+        assert_eq!(2, zxy_to_tile_id(1, 0, 1));
+        assert_eq!(3, zxy_to_tile_id(1, 1, 1));
+        assert_eq!(4, zxy_to_tile_id(1, 1, 0));
+        assert_eq!(5, zxy_to_tile_id(2, 0, 0));
+        assert_eq!(19078479, zxy_to_tile_id(12, 3423, 1763));
+    }
+
+    #[test]
+    fn test_pmtiles_header() {
+        let path = std::env::temp_dir().join("s57tiler_test.pmtiles");
+        let mut pm_tiles = PmTiles::create(&path);
+        pm_tiles.insert_tile(1, 1, 0, &[1, 2, 3]);
+        pm_tiles.insert_tile(1, 0, 0, &[1, 2, 3]);
+        pm_tiles.insert_tile(0, 0, 0, &[4, 5, 6]);
+        pm_tiles.finish(&TileMetadata {
+            name: String::from("US5WA22M"),
+            min_zoom: 0,
+            max_zoom: 1,
+            bounds: [-122.5, 47.2, -122.3, 47.3],
+            vector_layers: json!([]),
+        });
+        let archive = fs::read(&path).unwrap();
+        let u64_at = |i: usize| {
+            let mut bytes = [0_u8; 8];
+            bytes.copy_from_slice(&archive[i..i + 8]);
+            u64::from_le_bytes(bytes)
+        };
+        assert_eq!(b"PMTiles", &archive[0..7]);
+        assert_eq!(3, archive[7]);
+        assert_eq!(127, u64_at(8));
+        assert_eq!(3, u64_at(72)); // addressed tiles
+        assert_eq!(3, u64_at(80)); // tile entries
+        assert_eq!(2, u64_at(88)); // tile contents
+        assert_eq!(archive.len() as u64, u64_at(56) + u64_at(64));
+        assert_eq!(&[0, 1], &archive[100..102]);
+        fs::remove_file(&path).ok();
+    }
+}
@@ -0,0 +1,672 @@
+use std::collections::BTreeMap;
+use serde_json::{Value, Number, json};
+use crate::geojson_builder::JsonObject;
+use crate::lookups::LOOKUPS;
+use crate::csp::MarinerSettings;
+use crate::{depare, depcnt, sprites};
+
+/// S-52 presentation library drawing instructions
+/// https://iho.int/uploads/user/pubs/standards/s-52/S-52%20PresLib%20Ed%204.0.pdf (Part I, 9)
+///
+/// An instruction set is a `;` delimited list of commands eg `AC(LANDA);AP(AIRARE02);LS(SOLD,1,CHBLK)`
+#[derive(Debug, PartialEq)]
+pub enum Instruction {
+    /// SY(SYMBOL[,ROT]) point symbol, optionally rotated by an attribute value or degrees
+    Symbol { name: String, rotation: Option<String> },
+    /// LS(PSTYLE,WIDTH,COLOUR) simple line style SOLD, DASH or DOTT
+    SimpleLine { style: String, width: f64, colour: String },
+    /// LC(LINNAME) complex line style
+    ComplexLine { name: String },
+    /// AC(AREACOLOUR[,TRANSPARENCY]) area colour fill, transparency 0 - 3 (0%, 25%, 50%, 75%)
+    AreaColour { colour: String, transparency: u8 },
+    /// AP(PATNAME) area pattern fill
+    AreaPattern { name: String },
+    /// TX(STRING,HJUST,VJUST,SPACE,CHARS,XOFFS,YOFFS,COLOUR,DISPLAY) text of an attribute and
+    /// TE('FORMAT','ATTRIBUTES',HJUST,VJUST,SPACE,CHARS,XOFFS,YOFFS,COLOUR,DISPLAY) formatted text
+    Text { format: Option<String>, attributes: Vec<String>, colour: String },
+    /// CS(PROCNAME) conditional symbology procedure
+    Procedure { name: String },
+}
+
+/// Splits the arguments of a command on commas outside of single quotes, quotes are removed
+fn arguments(args: &str) -> Vec<String> {
+    let mut result = vec![];
+    let mut current = String::new();
+    let mut quoted = false;
+    for c in args.chars() {
+        match c {
+            '\'' => quoted = !quoted,
+            ',' if !quoted => result.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    result.push(current);
+    result.iter().map(|ea| ea.trim().to_string()).collect()
+}
+
+/// Parses an instruction set, unknown commands are skipped. The parsing is lenient with unbalanced
+/// parentheses found in some of the look-up table instructions.
+pub fn parse(instruction: &str) -> Vec<Instruction> {
+    instruction.split(';').filter_map(|command| {
+        let command = command.trim();
+        if command.len() < 3 || !command.is_char_boundary(2) {
+            return None;
+        }
+        let name = &command[0..2];
+        let args = arguments(command[3..].trim_end_matches(')'));
+        let arg = |i: usize| args.get(i).cloned().unwrap_or_default();
+        match name {
+            "SY" => Some(Instruction::Symbol {
+                name: arg(0),
+                rotation: args.get(1).cloned(),
+            }),
+            "LS" => Some(Instruction::SimpleLine {
+                style: arg(0),
+                width: arg(1).parse().unwrap_or(1.0),
+                colour: arg(2),
+            }),
+            "LC" => Some(Instruction::ComplexLine { name: arg(0) }),
+            "AC" => Some(Instruction::AreaColour {
+                colour: arg(0),
+                transparency: arg(1).parse().unwrap_or(0),
+            }),
+            "AP" => Some(Instruction::AreaPattern { name: arg(0) }),
+            "TX" => Some(Instruction::Text {
+                format: None,
+                attributes: vec![arg(0)],
+                colour: arg(7),
+            }),
+            "TE" => Some(Instruction::Text {
+                format: Some(arg(0)),
+                attributes: arg(1).split(',').map(String::from).collect(),
+                colour: arg(8),
+            }),
+            "CS" => Some(Instruction::Procedure { name: arg(0) }),
+            _ => None,
+        }
+    }).filter(|i| match i {
+        Instruction::Symbol { name, .. } | Instruction::ComplexLine { name } |
+        Instruction::AreaPattern { name } | Instruction::Procedure { name } => !name.is_empty(),
+        _ => true,
+    }).collect()
+}
+
+fn text_value(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) if !s.is_empty() => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Array(a) => Some(a.iter().filter_map(text_value).collect::<Vec<String>>().join(",")),
+        _ => None,
+    }
+}
+
+/// Formats the attribute values with a C printf style format eg `%s`, `%03.0lf` or `%4.1lf`
+pub fn format_text(format: &str, values: &[Option<&Value>]) -> Option<String> {
+    let mut result = String::new();
+    let mut values = values.iter();
+    let mut chars = format.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            result.push(c);
+            continue;
+        }
+        if chars.peek() == Some(&'%') {
+            chars.next();
+            result.push('%');
+            continue;
+        }
+        let mut spec = String::new();
+        for s in chars.by_ref() {
+            if s.is_ascii_alphabetic() && s != 'l' {
+                spec.push(s);
+                break;
+            }
+            spec.push(s);
+        }
+        let value = values.next().and_then(|v| *v)?;
+        let conversion = spec.chars().last().unwrap_or('s');
+        let spec = spec.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+        let zero_pad = spec.starts_with('0');
+        let mut parts = spec.trim_start_matches(&['0', '-', '+', ' '][..]).splitn(2, '.');
+        let width: usize = parts.next().and_then(|w| w.parse().ok()).unwrap_or(0);
+        let precision: Option<usize> = parts.next().and_then(|p| p.parse().ok());
+        let text = match conversion {
+            'f' | 'd' | 'i' => {
+                let number = value.as_f64().or_else(|| value.as_str().and_then(|s| s.parse().ok()))?;
+                let precision = if conversion == 'f' { precision.unwrap_or(6) } else { 0 };
+                if zero_pad {
+                    format!("{:0width$.precision$}", number, width = width, precision = precision)
+                } else {
+                    format!("{:width$.precision$}", number, width = width, precision = precision)
+                }
+            }
+            _ => format!("{:width$}", text_value(value)?, width = width),
+        };
+        result.push_str(&text);
+    }
+    Some(result)
+}
+
+/// Expands the instruction set into feature properties used by the generated style layers
+///     SY - symbol, SYRT - symbol rotation in degrees
+///     LSTY - line style SOLD, DASH or DOTT, LWID - line width, LCOL - line colour
+///     LC - complex line style, one without an image in the sprite sheet is drawn as a dash
+///     AC - area colour, ACOP - area colour opacity
+///     AP - area pattern, left out when the sprite sheet has no image for it
+///     TX - text, TXCL - text colour
+///     CS - conditional symbology procedure
+///
+/// A symbol selected by an object class processor is kept.
+pub fn expand(instructions: &[Instruction], properties: &mut JsonObject) {
+    let mut text: Vec<String> = vec![];
+    for instruction in instructions {
+        match instruction {
+            Instruction::Symbol { name, rotation } => {
+                if !properties.contains_key("SY") {
+                    properties.insert(String::from("SY"), Value::String(name.clone()));
+                }
+                let degrees = rotation.as_ref().and_then(|r| {
+                    r.parse::<f64>().ok().or_else(|| properties.get(r).and_then(|v| v.as_f64()))
+                });
+                if let Some(degrees) = degrees.and_then(Number::from_f64) {
+                    properties.insert(String::from("SYRT"), Value::Number(degrees));
+                }
+            }
+            Instruction::SimpleLine { style, width, colour } => {
+                properties.insert(String::from("LSTY"), Value::String(style.clone()));
+                properties.insert(String::from("LWID"), json!(width));
+                properties.insert(String::from("LCOL"), Value::String(colour.clone()));
+            }
+            Instruction::ComplexLine { name } => {
+                if COMPLEX_LINES.iter().any(|l| l.name == name) || sprites::has_symbol(name) {
+                    properties.insert(String::from("LC"), Value::String(name.clone()));
+                } else if !properties.contains_key("LSTY") {
+                    // a line style without an image in the sprite sheet is drawn as a plain dash
+                    properties.insert(String::from("LSTY"), json!("DASH"));
+                    properties.insert(String::from("LWID"), json!(1.0));
+                    properties.insert(String::from("LCOL"), json!("CHMGD"));
+                }
+            }
+            Instruction::AreaColour { colour, transparency } => {
+                properties.insert(String::from("AC"), Value::String(colour.clone()));
+                properties.insert(String::from("ACOP"), json!(1.0 - 0.25 * (*transparency).min(3) as f64));
+            }
+            Instruction::AreaPattern { name } => {
+                // areas whose pattern has no image in the sprite sheet are drawn with their fill
+                if sprites::has_symbol(name) {
+                    properties.insert(String::from("AP"), Value::String(name.clone()));
+                }
+            }
+            Instruction::Text { format, attributes, colour } => {
+                let values: Vec<Option<&Value>> = attributes.iter().map(|a| properties.get(a)).collect();
+                let value = match format {
+                    Some(format) => format_text(format, &values),
+                    None => values.first().and_then(|v| *v).and_then(text_value),
+                };
+                if let Some(value) = value {
+                    text.push(value);
+                    if !properties.contains_key("TXCL") {
+                        properties.insert(String::from("TXCL"), Value::String(colour.clone()));
+                    }
+                }
+            }
+            Instruction::Procedure { name } => {
+                properties.insert(String::from("CS"), Value::String(name.clone()));
+            }
+        }
+    }
+    if !text.is_empty() {
+        properties.insert(String::from("TX"), Value::String(text.join("\n")));
+    }
+}
+
+/// The kinds of style layer an object class needs, in drawing order within a display priority
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum LayerKind {
+    Fill,
+    Pattern,
+    Line(LineStyle),
+    ComplexLine,
+    Symbol,
+    Text,
+}
+
+impl LayerKind {
+    fn all() -> Vec<LayerKind> {
+        vec![
+            LayerKind::Fill,
+            LayerKind::Pattern,
+            LayerKind::Line(LineStyle::Solid),
+            LayerKind::Line(LineStyle::Dash),
+            LayerKind::Line(LineStyle::Dot),
+            LayerKind::ComplexLine,
+            LayerKind::Symbol,
+            LayerKind::Text,
+        ]
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum LineStyle {
+    Solid,
+    Dash,
+    Dot,
+}
+
+impl LineStyle {
+    fn from_name(name: &str) -> LineStyle {
+        match name {
+            "DASH" => LineStyle::Dash,
+            "DOTT" => LineStyle::Dot,
+            _ => LineStyle::Solid,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            LineStyle::Solid => "SOLD",
+            LineStyle::Dash => "DASH",
+            LineStyle::Dot => "DOTT",
+        }
+    }
+}
+
+/// The line of a complex line style
+#[derive(PartialEq)]
+enum Stroke {
+    Dash,
+    Solid,
+}
+
+/// An S-52 complex line style drawn as a dashed or solid line, T-marks facing into the
+/// area and a repeated symbol instead of a line pattern
+struct ComplexLineStyle {
+    name: &'static str,
+    colour: &'static str,
+    stroke: Stroke,
+    tmarks: bool,
+    symbol: Option<&'static str>,
+}
+
+const fn complex_line(
+    name: &'static str,
+    colour: &'static str,
+    stroke: Stroke,
+    tmarks: bool,
+    symbol: Option<&'static str>,
+) -> ComplexLineStyle {
+    ComplexLineStyle { name, colour, stroke, tmarks, symbol }
+}
+
+const COMPLEX_LINES: [ComplexLineStyle; 20] = [
+    // restricted and regulated areas
+    complex_line("RESARE51", "CHMGD", Stroke::Dash, true, None),
+    complex_line("ENTRES51", "CHMGD", Stroke::Dash, true, Some("ENTRES51")),
+    complex_line("ACHRES51", "CHMGD", Stroke::Dash, true, Some("ACHRES51")),
+    complex_line("FSHRES51", "CHMGD", Stroke::Dash, true, Some("FSHRES51")),
+    complex_line("ESSARE01", "CHMGD", Stroke::Dash, true, None),
+    complex_line("ACHARE51", "CHMGD", Stroke::Dash, false, Some("ACHARE51")),
+    complex_line("CTYARE51", "CHMGD", Stroke::Dash, false, Some("CTYARE51")),
+    complex_line("CTNARE51", "CHMGD", Stroke::Dash, false, Some("CTNARE51")),
+    // routeing measures, recommended tracks are solid when based on a system of fixed marks
+    complex_line("DWRUTE51", "TRFCD", Stroke::Dash, false, None),
+    complex_line("NAVARE51", "CHGRD", Stroke::Dash, false, None),
+    complex_line("RECTRC09", "CHBLK", Stroke::Dash, false, None),
+    complex_line("RECTRC10", "CHBLK", Stroke::Solid, false, None),
+    complex_line("RECTRC11", "CHBLK", Stroke::Dash, false, None),
+    complex_line("RECTRC12", "CHBLK", Stroke::Solid, false, None),
+    complex_line("RECDEF02", "CHBLK", Stroke::Dash, false, None),
+    // submarine pipelines, oil and gas pipelines are magenta, cables CBLSUB06 are a line pattern
+    complex_line("PIPSOL05", "CHMGD", Stroke::Dash, false, None),
+    complex_line("PIPSOL06", "CHGRD", Stroke::Dash, false, None),
+    complex_line("CBLARE51", "CHMGD", Stroke::Dash, true, Some("CBLARE51")),
+    complex_line("PIPARE51", "CHMGD", Stroke::Dash, true, None),
+    complex_line("PIPARE61", "CHGRD", Stroke::Dash, true, None),
+];
+
+/// A palette colour lookup expression for a property holding a colour token eg CHBLK
+fn palette(colors: &Value, property: &str) -> Value {
+    let mut expression = vec![json!("match"), json!(["get", property])];
+    if let Some(tokens) = colors.as_object() {
+        for (token, color) in tokens {
+            expression.push(json!(token));
+            expression.push(color.clone());
+        }
+    }
+    expression.push(colors["CHBLK"].clone());
+    Value::Array(expression)
+}
+
+fn style_layer(class: &str, kind: LayerKind, colors: &Value, settings: &MarinerSettings) -> Value {
+    let mut layer = base_style_layer(class, kind, colors);
+    // depth shades and the safety contour follow the mariner's settings of the style, areas without
+    // a depth range keep the colour of the look-up eg NODTA and contours are drawn as ordinary
+    // contours when they are not the safety contour of the style
+    match (class, kind) {
+        ("DEPARE", LayerKind::Fill) | ("DRGARE", LayerKind::Fill) => {
+            layer["paint"]["fill-color"] = json!([
+                "case", ["has", "DRVAL1"], depare::fill_colour(colors, settings), palette(colors, "AC")
+            ]);
+        }
+        ("DEPARE", LayerKind::Line(_)) | ("DEPCNT", LayerKind::Line(_)) => {
+            let safety_contour = depcnt::is_safety_contour(settings);
+            layer["paint"]["line-color"] = json!(["case", safety_contour, colors["DEPSC"], colors["DEPCN"]]);
+            layer["paint"]["line-width"] = json!(["case", safety_contour, 2, 1]);
+        }
+        _ => {}
+    }
+    layer
+}
+
+fn base_style_layer(class: &str, kind: LayerKind, colors: &Value) -> Value {
+    match kind {
+        LayerKind::Fill => json!({
+            "id": format!("{}_fill", class),
+            "type": "fill",
+            "source": "src_senc",
+            "source-layer": class,
+            "filter": ["all", ["==", "$type", "Polygon"], ["has", "AC"]],
+            "paint": {
+                "fill-color": palette(colors, "AC"),
+                "fill-opacity": ["coalesce", ["get", "ACOP"], 1]
+            }
+        }),
+        LayerKind::Pattern => json!({
+            "id": format!("{}_pattern", class),
+            "type": "fill",
+            "source": "src_senc",
+            "source-layer": class,
+            "filter": ["all", ["==", "$type", "Polygon"], ["has", "AP"]],
+            "paint": {
+                "fill-pattern": ["get", "AP"]
+            }
+        }),
+        LayerKind::Line(style) => {
+            let mut layer = json!({
+                "id": format!("{}_line_{}", class, style.name()),
+                "type": "line",
+                "source": "src_senc",
+                "source-layer": class,
+                "filter": ["all", ["!=", "$type", "Point"], ["==", "LSTY", style.name()]],
+                "paint": {
+                    "line-color": palette(colors, "LCOL"),
+                    "line-width": ["coalesce", ["get", "LWID"], 1]
+                }
+            });
+            match style {
+                LineStyle::Dash => layer["paint"]["line-dasharray"] = json!([4, 2]),
+                LineStyle::Dot => layer["paint"]["line-dasharray"] = json!([1, 2]),
+                LineStyle::Solid => {}
+            }
+            layer
+        }
+        LayerKind::ComplexLine => json!({
+            "id": format!("{}_complex_line", class),
+            "type": "line",
+            "source": "src_senc",
+            "source-layer": class,
+            "filter": ["all", ["!=", "$type", "Point"], ["has", "LC"],
+                membership("!in", COMPLEX_LINES.iter().map(|l| l.name).collect())],
+            "paint": {
+                "line-pattern": ["get", "LC"],
+                "line-width": 8
+            }
+        }),
+        LayerKind::Symbol => json!({
+            "id": format!("{}_symbol", class),
+            "type": "symbol",
+            "source": "src_senc",
+            "source-layer": class,
+            "filter": ["has", "SY"],
+            "layout": {
+                "icon-image": ["get", "SY"],
+                "icon-rotate": ["coalesce", ["get", "SYRT"], 0],
+                "icon-rotation-alignment": "map",
+                "icon-allow-overlap": true,
+                "icon-ignore-placement": true,
+                "symbol-placement": "point"
+            }
+        }),
+        LayerKind::Text => json!({
+            "id": format!("{}_text", class),
+            "type": "symbol",
+            "source": "src_senc",
+            "source-layer": class,
+            "filter": ["has", "TX"],
+            "layout": {
+                "text-font": ["Roboto Bold"],
+                "text-field": ["get", "TX"],
+                "text-size": 11,
+                "text-anchor": "top",
+                "text-offset": [0, 0.8],
+                "text-max-width": 9,
+                "text-allow-overlap": false,
+                "symbol-placement": "point"
+            },
+            "paint": {
+                "text-color": palette(colors, "TXCL"),
+                "text-halo-color": *crate::colors::TXT_FG,
+                "text-halo-width": 1
+            }
+        }),
+    }
+}
+
+/// A legacy `in` / `!in` filter of the complex line style `LC`
+fn membership(operator: &str, names: Vec<&str>) -> Value {
+    let mut filter = vec![json!(operator), json!("LC")];
+    filter.extend(names.into_iter().map(|name| json!(name)));
+    Value::Array(filter)
+}
+
+/// The layers of the complex line styles of `COMPLEX_LINES`. Exterior rings are wound clockwise
+/// on screen by the tiler so a positive offset draws the T-marks inside the area.
+fn complex_line_layers(class: &str, colors: &Value) -> Vec<Value> {
+    let names = |f: fn(&ComplexLineStyle) -> bool| -> Vec<&str> {
+        COMPLEX_LINES.iter().filter(|l| f(l)).map(|l| l.name).collect()
+    };
+    let filter = |names: Vec<&str>| json!(["all", ["!=", "$type", "Point"], membership("in", names)]);
+    let mut line_color = vec![json!("match"), json!(["get", "LC"])];
+    let mut icon_image = vec![json!("match"), json!(["get", "LC"])];
+    for line in COMPLEX_LINES.iter() {
+        line_color.push(json!(line.name));
+        line_color.push(colors[line.colour].clone());
+        if let Some(symbol) = line.symbol {
+            icon_image.push(json!(line.name));
+            icon_image.push(json!(symbol));
+        }
+    }
+    line_color.push(colors["CHMGD"].clone());
+    icon_image.push(json!(""));
+    vec![
+        json!({
+            "id": format!("{}_complex_line_dash", class),
+            "type": "line",
+            "source": "src_senc",
+            "source-layer": class,
+            "filter": filter(names(|l| l.stroke == Stroke::Dash)),
+            "paint": {
+                "line-color": line_color.clone(),
+                "line-width": 1,
+                "line-dasharray": [4, 2]
+            }
+        }),
+        json!({
+            "id": format!("{}_complex_line_solid", class),
+            "type": "line",
+            "source": "src_senc",
+            "source-layer": class,
+            "filter": filter(names(|l| l.stroke == Stroke::Solid)),
+            "paint": {
+                "line-color": line_color.clone(),
+                "line-width": 1
+            }
+        }),
+        json!({
+            "id": format!("{}_complex_line_tmark", class),
+            "type": "line",
+            "source": "src_senc",
+            "source-layer": class,
+            "filter": filter(names(|l| l.tmarks)),
+            "paint": {
+                "line-color": line_color,
+                "line-width": 4,
+                "line-offset": 2,
+                "line-dasharray": [0.25, 6]
+            }
+        }),
+        json!({
+            "id": format!("{}_complex_line_symbol", class),
+            "type": "symbol",
+            "source": "src_senc",
+            "source-layer": class,
+            "filter": filter(names(|l| l.symbol.is_some())),
+            "layout": {
+                "icon-image": icon_image,
+                "icon-size": 0.5,
+                "icon-allow-overlap": true,
+                "symbol-placement": "line",
+                "symbol-spacing": 200
+            }
+        }),
+    ]
+}
+
+/// Style layers for every object class of the look-up tables compiled from the kinds of
+/// instruction the class uses. Layers are ordered by S-52 display priority and then by kind so
+/// area fills are drawn below lines, symbols and text of the same priority.
+pub fn layers(colors: &Value, settings: &MarinerSettings) -> Vec<Value> {
+    let mut kinds: BTreeMap<(String, LayerKind), u8> = BTreeMap::new();
+    for lookup in LOOKUPS.iter().filter(|l| l.name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')) {
+        for instruction in parse(&lookup.instruction) {
+            let instruction_kinds = match instruction {
+                Instruction::AreaColour { .. } => vec![LayerKind::Fill],
+                Instruction::AreaPattern { .. } => vec![LayerKind::Pattern],
+                Instruction::SimpleLine { style, .. } => vec![LayerKind::Line(LineStyle::from_name(&style))],
+                Instruction::ComplexLine { .. } => vec![LayerKind::ComplexLine, LayerKind::Line(LineStyle::Dash)],
+                Instruction::Symbol { .. } => vec![LayerKind::Symbol],
+                Instruction::Text { .. } => vec![LayerKind::Text],
+                // the instructions of a conditional symbology procedure are only known per feature
+                Instruction::Procedure { .. } => LayerKind::all(),
+            };
+            for kind in instruction_kinds {
+                let priority = kinds.entry((lookup.name.clone(), kind)).or_insert(lookup.display_priority);
+                *priority = (*priority).min(lookup.display_priority);
+            }
+        }
+    }
+    let mut ordered: Vec<(u8, LayerKind, String)> = kinds.into_iter()
+        .map(|((class, kind), priority)| (priority, kind, class))
+        .collect();
+    ordered.sort();
+    ordered.iter().flat_map(|(_, kind, class)| {
+        let mut layers = vec![style_layer(class, *kind, colors, settings)];
+        if *kind == LayerKind::ComplexLine {
+            layers.extend(complex_line_layers(class, colors));
+        }
+        layers
+    }).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(vec![
+            Instruction::AreaColour { colour: String::from("LANDA"), transparency: 0 },
+            Instruction::AreaPattern { name: String::from("AIRARE02") },
+            Instruction::SimpleLine { style: String::from("SOLD"), width: 1.0, colour: String::from("CHBLK") },
+        ], parse("AC(LANDA);AP(AIRARE02);LS(SOLD,1,CHBLK)"));
+        assert_eq!(vec![
+            Instruction::Symbol { name: String::from("LNDARE01"), rotation: None },
+            Instruction::Procedure { name: String::from("QUAPOS01") },
+            Instruction::Text { format: None, attributes: vec![String::from("OBJNAM")], colour: String::from("CHBLK") },
+        ], parse("SY(LNDARE01);CS(QUAPOS01;TX(OBJNAM,1,2,3,'15118',-1,-1,CHBLK,26))"));
+        assert_eq!(vec![
+            Instruction::Text {
+                format: Some(String::from("by %s")),
+                attributes: vec![String::from("OBJNAM")],
+                colour: String::from("CHBLK"),
+            },
+        ], parse("TE('by %s','OBJNAM',2,1,2,'15110',-1,-1,CHBLK,21)"));
+    }
+
+    #[test]
+    fn test_format_text() {
+        let orient = json!(45.5);
+        let name = json!("Foo");
+        assert_eq!(Some(String::from("046 deg")), format_text("%03.0lf deg", &[Some(&orient)]));
+        assert_eq!(Some(String::from("Foo")), format_text("%s", &[Some(&name)]));
+        assert_eq!(Some(String::from("clr 45.5")), format_text("clr %4.1lf", &[Some(&orient)]));
+        assert_eq!(None, format_text("%s", &[None]));
+    }
+
+    #[test]
+    fn test_expand() {
+        let mut properties = json!({"ORIENT": 90.0, "OBJNAM": "Foo"}).as_object().unwrap().clone();
+        expand(&parse("SY(TSSLPT51,ORIENT);AC(TRFCF,3);LS(DASH,2,TRFCD);TX(OBJNAM,1,2,3,'15110',0,0,CHBLK,29)"), &mut properties);
+        assert_eq!(json!("TSSLPT51"), properties["SY"]);
+        assert_eq!(json!(90.0), properties["SYRT"]);
+        assert_eq!(json!("TRFCF"), properties["AC"]);
+        assert_eq!(json!(0.25), properties["ACOP"]);
+        assert_eq!(json!("DASH"), properties["LSTY"]);
+        assert_eq!(json!("Foo"), properties["TX"]);
+    }
+
+    #[test]
+    fn test_expand_missing_sprites() {
+        let mut properties = JsonObject::new();
+        expand(&parse("AC(DEPVS);AP(DIAMOND1);LC(HODATA01)"), &mut properties);
+        assert!(properties.get("AP").is_none());
+        assert!(properties.get("LC").is_none());
+        assert_eq!(json!("DASH"), properties["LSTY"]);
+        let mut properties = JsonObject::new();
+        expand(&parse("AP(AIRARE02);LC(CTNARE51)"), &mut properties);
+        assert_eq!(json!("AIRARE02"), properties["AP"]);
+        assert_eq!(json!("CTNARE51"), properties["LC"]);
+    }
+
+    /// The output of a `match` expression for the label
+    fn match_output<'a>(expression: &'a Value, label: &str) -> Option<&'a Value> {
+        let arms = expression.as_array().filter(|e| e.len() > 2 && e[0] == "match")?;
+        arms[2..arms.len() - 1].chunks(2).find(|arm| arm[0] == label).map(|arm| &arm[1])
+    }
+
+    #[test]
+    fn test_layers() {
+        let colors = crate::colors::COLORS["DAY_BRIGHT"].clone();
+        let layers = layers(&colors, &MarinerSettings::default());
+        let position = |id: &str| layers.iter().position(|l| l["id"] == id).unwrap();
+        assert!(position("LNDARE_fill") < position("PONTON_fill"));
+        assert!(position("PONTON_fill") < position("PONTON_line_SOLD"));
+        assert!(position("PONTON_line_SOLD") < position("BOYCAR_symbol"));
+        assert_eq!(json!([4, 2]), layers[position("TSSBND_line_DASH")]["paint"]["line-dasharray"]);
+        // classes symbolized by procedures get every kind of layer
+        assert!(position("DEPARE_fill") < position("DEPCNT_line_SOLD"));
+        assert!(position("LIGHTS_symbol") > 0);
+        for aid in &["BOYLAT", "BOYCAR", "BOYISD", "BOYSAW", "BOYINB", "BOYSPP",
+            "BCNLAT", "BCNCAR", "BCNISD", "BCNSAW", "BCNSPP"] {
+            assert!(position(&format!("{}_symbol", aid)) > 0);
+        }
+        // restricted area boundaries are dashed with T-marks and symbols instead of a pattern
+        assert!(position("RESARE_complex_line") < position("RESARE_complex_line_tmark"));
+        let tmark = &layers[position("RESARE_complex_line_tmark")];
+        assert_eq!(json!(2), tmark["paint"]["line-offset"]);
+        let icon_image = &layers[position("CTNARE_complex_line_symbol")]["layout"]["icon-image"];
+        assert_eq!(Some(&json!("CTNARE51")), match_output(icon_image, "CTNARE51"));
+        // fairway boundaries and recommended tracks
+        let dash = &layers[position("FAIRWY_complex_line_dash")];
+        assert!(dash["filter"][2].as_array().unwrap().contains(&json!("NAVARE51")));
+        assert!(!layers[position("CBLSUB_complex_line")]["filter"][3].as_array().unwrap().contains(&json!("CBLSUB06")));
+        assert!(layers[position("RECTRC_complex_line_solid")]["filter"][2].as_array().unwrap().contains(&json!("RECTRC10")));
+        let fill = &layers[position("DEPARE_fill")]["paint"]["fill-color"];
+        assert_eq!(json!(["has", "DRVAL1"]), fill[1]);
+        assert_eq!("match", fill[3][0]);
+        let contour = &layers[position("DEPCNT_line_SOLD")]["paint"];
+        assert_eq!(colors["DEPSC"], contour["line-color"][2]);
+        assert_eq!(colors["DEPCN"], contour["line-color"][3]);
+        assert_eq!(json!(1), contour["line-width"][3]);
+    }
+}
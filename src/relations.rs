@@ -0,0 +1,180 @@
+use std::collections::HashMap;
+use serde_json::{Value, json};
+use crate::csp;
+use crate::geojson_builder::JsonObject;
+
+/// The feature's master or the collection it is a member of
+pub const PARENT_LNAM: &str = "PARENT_LNAM";
+/// The feature's slaves or the members of a collection
+pub const CHILDREN: &str = "CHILDREN";
+
+/// FFPT_RIND relationship indicator of a referenced feature
+///     1	master
+///     2	slave
+///     3	peer
+const MASTER: i64 = 1;
+const SLAVE: i64 = 2;
+
+/// Collection objects, their members are referenced as peers
+const COLLECTIONS: [&str; 3] = ["C_AGGR", "C_ASSO", "C_STAC"];
+
+/// Feature to feature relationships (FFPT) of a cell keyed by the features' long names (LNAM).
+///
+/// Equipment is encoded as slaves of the structure it is attached to eg a light, topmark or
+/// fog signal on a buoy or beacon. Collection objects (C_AGGR, C_ASSO) reference their members.
+#[derive(Default)]
+pub struct Relations {
+    /// object class of every feature
+    classes: HashMap<String, String>,
+    /// master of a slave feature
+    masters: HashMap<String, String>,
+    /// slaves of a master feature
+    slaves: HashMap<String, Vec<String>>,
+    /// collections the feature is a member of
+    collections: HashMap<String, Vec<String>>,
+    /// members of a collection
+    members: HashMap<String, Vec<String>>,
+}
+
+impl Relations {
+    /// Indexes the feature's LNAM and its references LNAM_REFS with their relationship
+    /// indicators FFPT_RIND
+    pub fn add(&mut self, class: &str, properties: &JsonObject) {
+        let lnam = match properties.get("LNAM").and_then(|v| v.as_str()) {
+            Some(lnam) => lnam.to_string(),
+            None => return,
+        };
+        self.classes.insert(lnam.clone(), class.to_string());
+        let indicators = csp::list_values(properties, "FFPT_RIND");
+        for (i, reference) in lnam_refs(properties).into_iter().enumerate() {
+            if COLLECTIONS.contains(&class) {
+                push(&mut self.members, &lnam, &reference);
+                push(&mut self.collections, &reference, &lnam);
+                continue;
+            }
+            match indicators.get(i).cloned() {
+                Some(SLAVE) => {
+                    self.masters.insert(reference.clone(), lnam.clone());
+                    push(&mut self.slaves, &lnam, &reference);
+                }
+                Some(MASTER) => {
+                    self.masters.insert(lnam.clone(), reference.clone());
+                    push(&mut self.slaves, &reference, &lnam);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    pub fn class(&self, lnam: &str) -> Option<&str> {
+        self.classes.get(lnam).map(String::as_str)
+    }
+
+    pub fn master(&self, lnam: &str) -> Option<&str> {
+        self.masters.get(lnam).map(String::as_str)
+    }
+
+    pub fn slaves(&self, lnam: &str) -> &[String] {
+        self.slaves.get(lnam).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    pub fn collections(&self, lnam: &str) -> &[String] {
+        self.collections.get(lnam).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    pub fn members(&self, lnam: &str) -> &[String] {
+        self.members.get(lnam).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// The slaves of the feature of an object class eg the LIGHTS on a buoy
+    pub fn slaves_of_class(&self, lnam: &str, class: &str) -> Vec<&str> {
+        self.slaves(lnam).iter()
+            .filter(|slave| self.class(slave) == Some(class))
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// Writes the feature's master or first collection `PARENT_LNAM` and its slaves and members
+    /// `CHILDREN`
+    pub fn annotate(&self, properties: &mut JsonObject) {
+        let lnam = match properties.get("LNAM").and_then(|v| v.as_str()) {
+            Some(lnam) => lnam.to_string(),
+            None => return,
+        };
+        let parent = self.master(&lnam).or_else(|| self.collections(&lnam).first().map(String::as_str));
+        if let Some(parent) = parent {
+            properties.insert(String::from(PARENT_LNAM), json!(parent));
+        }
+        let children: Vec<&String> = self.slaves(&lnam).iter().chain(self.members(&lnam).iter()).collect();
+        if !children.is_empty() {
+            properties.insert(String::from(CHILDREN), json!(children));
+        }
+    }
+}
+
+fn push(map: &mut HashMap<String, Vec<String>>, key: &str, value: &str) {
+    let values = map.entry(key.to_string()).or_default();
+    if !values.iter().any(|v| v == value) {
+        values.push(value.to_string());
+    }
+}
+
+/// The LNAM of the features the feature references (LNAM_REFS), a list or a comma separated string
+fn lnam_refs(properties: &JsonObject) -> Vec<String> {
+    match properties.get("LNAM_REFS") {
+        Some(Value::Array(refs)) => refs.iter().filter_map(|v| v.as_str()).map(String::from).collect(),
+        Some(Value::String(refs)) => refs.split(',').map(|r| r.trim().to_string()).collect(),
+        _ => vec![],
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn properties(value: Value) -> JsonObject {
+        value.as_object().unwrap().clone()
+    }
+
+    /// A special purpose beacon of US5WA22M with its light and daymark, and an aggregation
+    fn relations() -> Relations {
+        let mut relations = Relations::default();
+        relations.add("BCNSPP", &properties(json!({
+            "LNAM": "022609A44B1C0001",
+            "LNAM_REFS": ["022609A44B1C0002", "022609A44B1C0003"],
+            "FFPT_RIND": [2, 2]
+        })));
+        relations.add("LIGHTS", &properties(json!({"LNAM": "022609A44B1C0002"})));
+        relations.add("DAYMAR", &properties(json!({"LNAM": "022609A44B1C0003"})));
+        relations.add("C_AGGR", &properties(json!({
+            "LNAM": "022609A44B1C0004",
+            "LNAM_REFS": "022609A44B1C0001,022609A44B1C0005",
+            "FFPT_RIND": [3, 3]
+        })));
+        relations
+    }
+
+    #[test]
+    fn test_relations() {
+        let relations = relations();
+        assert_eq!(Some("022609A44B1C0001"), relations.master("022609A44B1C0002"));
+        assert_eq!(vec!["022609A44B1C0002"], relations.slaves_of_class("022609A44B1C0001", "LIGHTS"));
+        assert_eq!(Some("DAYMAR"), relations.class("022609A44B1C0003"));
+        assert_eq!(["022609A44B1C0004"], relations.collections("022609A44B1C0001"));
+        assert_eq!(2, relations.members("022609A44B1C0004").len());
+        assert!(relations.slaves("022609A44B1C0002").is_empty());
+    }
+
+    #[test]
+    fn test_annotate() {
+        let relations = relations();
+        let mut beacon = properties(json!({"LNAM": "022609A44B1C0001"}));
+        relations.annotate(&mut beacon);
+        assert_eq!(json!("022609A44B1C0004"), beacon[PARENT_LNAM]);
+        assert_eq!(json!(["022609A44B1C0002", "022609A44B1C0003"]), beacon[CHILDREN]);
+        let mut light = properties(json!({"LNAM": "022609A44B1C0002"}));
+        relations.annotate(&mut light);
+        assert_eq!(json!("022609A44B1C0001"), light[PARENT_LNAM]);
+        assert!(light.get(CHILDREN).is_none());
+    }
+}
@@ -0,0 +1,155 @@
+use geojson::Value as GeoValue;
+use crate::s57::ChartLayer;
+
+/// Regulated and restricted areas whose symbol and text are centered in the area
+pub const REGULATED: [&str; 16] = [
+    "ACHARE", "ACHBRT", "CBLARE", "CTNARE", "DMPGRD", "FSHZNE", "ICNARE", "ISTZNE", "MARCUL",
+    "MIPARE", "OSPARE", "PIPARE", "PRCARE", "RESARE", "SPLARE", "TESARE",
+];
+
+/// Presentation properties drawn at the label point instead of the area
+pub const LABEL_PROPERTIES: [&str; 4] = ["SY", "SYRT", "TX", "TXCL"];
+
+/// RESARE, Restricted area
+/// Geometric primitives: Area
+///
+/// Set Attribute_A: 	CATREA; DATEND; DATSTA; NOBJNM; OBJNAM; PEREND; PERSTA; RESTRN; SCAMIN; STATUS;
+/// Set Attribute_B: 	INFORM; NINFOM; NTXTDS; PICREP; SCAMAX; TXTDSC;
+/// Set Attribute_C: 	RECDAT; RECIND; SORDAT; SORIND;
+///
+/// Definition:
+///     A specified area designated by an appropriate authority within which navigation is restricted in accordance with certain specified conditions. (IHO Dictionary, S-32, 5th Edition, 4359)
+/// References
+///     INT 1:	IN 2.1, 20-22, 25, 26, 31;	IL 4;	IQ 57-59;
+///     S-4:	439.2-3; 439.5-6; 448.2; 449.1-2; 449.6; 445.2-3; 431.1;
+/// Remarks:
+///     The boundary is drawn as a dashed line with T-marks facing into the area, the restriction
+///     symbol is drawn at its center.
+/// Distinction:
+///     anchorage area; caution area; military practice area; fishing area;
+///
+/// Adds a point inside every symbolized area of the classes eg `REGULATED` carrying the area's
+/// properties, the symbol `SY` and text `TX` are moved from the area to the point so they are
+/// drawn once at its center instead of at the polygon's position.
+pub fn label_points(layers: &mut [ChartLayer], classes: &[&str]) {
+    for layer in layers.iter_mut().filter(|l| classes.contains(&l.name.as_str())) {
+        let mut points = vec![];
+        for feature in layer.collection.features.iter_mut() {
+            if let (Some(geometry), Some(properties)) = (&feature.geometry, &mut feature.properties) {
+                if !properties.contains_key("SY") && !properties.contains_key("TX") {
+                    continue;
+                }
+                if let Some(point) = label_point(&geometry.value) {
+                    let label = properties.clone();
+                    for key in LABEL_PROPERTIES.iter() {
+                        properties.remove(*key);
+                    }
+                    points.push(geojson::Feature {
+                        bbox: None,
+                        geometry: Some(geojson::Geometry::new(GeoValue::Point(point))),
+                        id: None,
+                        properties: Some(label),
+                        foreign_members: None,
+                    });
+                }
+            }
+        }
+        layer.collection.features.extend(points);
+    }
+}
+
+/// A point inside the polygon or the largest polygon of a multi polygon. The centroid of the
+/// exterior ring when it is inside the area, otherwise the middle of the widest interval inside
+/// the area on the centroid's parallel eg for a U shaped area.
+pub fn label_point(geometry: &GeoValue) -> Option<Vec<f64>> {
+    let polygon = match geometry {
+        GeoValue::Polygon(rings) => rings,
+        GeoValue::MultiPolygon(polygons) => polygons.iter()
+            .filter(|p| !p.is_empty())
+            .max_by(|a, b| area(&a[0]).abs().partial_cmp(&area(&b[0]).abs()).unwrap())?,
+        _ => return None,
+    };
+    let centroid = centroid(polygon.first()?)?;
+    if contains(polygon, &centroid) {
+        return Some(centroid);
+    }
+    let mut crossings: Vec<f64> = polygon.iter()
+        .flat_map(|ring| ring.windows(2))
+        .filter(|edge| (edge[0][1] > centroid[1]) != (edge[1][1] > centroid[1]))
+        .map(|edge| {
+            let t = (centroid[1] - edge[0][1]) / (edge[1][1] - edge[0][1]);
+            edge[0][0] + t * (edge[1][0] - edge[0][0])
+        })
+        .collect();
+    crossings.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    crossings.chunks(2)
+        .filter(|interval| interval.len() == 2)
+        .max_by(|a, b| (a[1] - a[0]).partial_cmp(&(b[1] - b[0])).unwrap())
+        .map(|interval| vec![(interval[0] + interval[1]) / 2.0, centroid[1]])
+}
+
+/// Signed area of a closed ring (shoelace formula)
+fn area(ring: &[Vec<f64>]) -> f64 {
+    ring.windows(2).map(|e| e[0][0] * e[1][1] - e[1][0] * e[0][1]).sum::<f64>() / 2.0
+}
+
+fn centroid(ring: &[Vec<f64>]) -> Option<Vec<f64>> {
+    let a = area(ring);
+    if a == 0.0 {
+        return ring.first().cloned();
+    }
+    let (x, y) = ring.windows(2).fold((0.0, 0.0), |(x, y), e| {
+        let cross = e[0][0] * e[1][1] - e[1][0] * e[0][1];
+        (x + (e[0][0] + e[1][0]) * cross, y + (e[0][1] + e[1][1]) * cross)
+    });
+    Some(vec![x / (6.0 * a), y / (6.0 * a)])
+}
+
+/// Even-odd point in polygon test over the exterior and interior rings
+fn contains(polygon: &[Vec<Vec<f64>>], point: &[f64]) -> bool {
+    polygon.iter()
+        .flat_map(|ring| ring.windows(2))
+        .filter(|e| (e[0][1] > point[1]) != (e[1][1] > point[1]))
+        .filter(|e| point[0] < e[0][0] + (point[1] - e[0][1]) / (e[1][1] - e[0][1]) * (e[1][0] - e[0][0]))
+        .count() % 2 == 1
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+    use crate::test_util::chart_layer;
+
+    fn ring(points: &[(f64, f64)]) -> Vec<Vec<f64>> {
+        points.iter().map(|(x, y)| vec![*x, *y]).collect()
+    }
+
+    #[test]
+    fn test_label_point() {
+        let square = GeoValue::Polygon(vec![ring(&[(0.0, 0.0), (0.0, 2.0), (2.0, 2.0), (2.0, 0.0), (0.0, 0.0)])]);
+        assert_eq!(Some(vec![1.0, 1.0]), label_point(&square));
+        // the centroid of a U shaped area is in its opening
+        let u = GeoValue::Polygon(vec![ring(&[
+            (0.0, 0.0), (0.0, 4.0), (1.0, 4.0), (1.0, 1.0), (3.0, 1.0), (3.0, 4.0), (4.0, 4.0), (4.0, 0.0), (0.0, 0.0)
+        ])]);
+        let point = label_point(&u).unwrap();
+        assert!(contains(match &u { GeoValue::Polygon(p) => p, _ => unreachable!() }, &point));
+        assert_eq!(None, label_point(&GeoValue::Point(vec![0.0, 0.0])));
+    }
+
+    #[test]
+    fn test_label_points() {
+        let mut layers = vec![chart_layer("RESARE", vec![(
+            GeoValue::Polygon(vec![ring(&[(0.0, 0.0), (0.0, 2.0), (2.0, 2.0), (2.0, 0.0), (0.0, 0.0)])]),
+            json!({"SY": "ENTRES51", "LC": "ENTRES51", "RESTRN": ["7"]}),
+        )])];
+        label_points(&mut layers, &REGULATED);
+        let features = &layers[0].collection.features;
+        assert_eq!(2, features.len());
+        let area = features[0].properties.as_ref().unwrap();
+        assert!(area.get("SY").is_none());
+        assert_eq!(json!("ENTRES51"), area["LC"]);
+        assert_eq!(json!("ENTRES51"), features[1].properties.as_ref().unwrap()["SY"]);
+        assert_eq!(Some(geojson::Geometry::new(GeoValue::Point(vec![1.0, 1.0]))), features[1].geometry);
+    }
+}
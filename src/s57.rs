@@ -1,14 +1,26 @@
 use std::path::{Path, PathBuf};
 use gdal::Dataset;
 use serde_json;
-use std::fs;
-use crate::geojson_builder::feature_collection_from_layer;
+use crate::geojson_builder::{feature_collection_from_layer, gdal_feature_properties};
 use gdal::spatial_ref::SpatialRef;
-use std::process::Command;
-use std::collections::HashSet;
-use crate::utils;
+use std::collections::{HashSet, BTreeMap};
+use crate::{utils, tiler, lookups, lights, topmar, resare, tsslpt, bridge, dates, geojson_builder};
+use crate::csp::MarinerSettings;
+use crate::relations::Relations;
+use crate::units::Units;
+use crate::mbtiles::MbTiles;
+use crate::pmtiles::PmTiles;
+use crate::tiler::TileMetadata;
 use std::ffi::CString;
+use gdal_sys::OGRFieldType;
 
+/// The rendered features of a single S57 layer eg SOUNDG
+pub struct ChartLayer {
+    pub name: String,
+    /// attribute field names and their TileJSON types (Number, String, Boolean)
+    pub fields: BTreeMap<String, String>,
+    pub collection: geojson::FeatureCollection,
+}
 
 pub struct S57 {
     dataset: Dataset
@@ -18,7 +30,9 @@ impl S57 {
     pub fn open(path: &Path) -> Option<S57> {
         let key = CString::new("OGR_S57_OPTIONS").unwrap();
         // https://gdal.org/drivers/vector/s57.html
-        let value = CString::new("SPLIT_MULTIPOINT:ON,ADD_SOUNDG_DEPTH=OFF,UPDATES=APPLY,LIST_AS_STRING=OFF").unwrap();
+        // LNAM_REFS adds the feature to feature relationships LNAM, LNAM_REFS and FFPT_RIND, the
+        // spatial linkages of RETURN_LINKAGES are not needed as features are read with geometry
+        let value = CString::new("SPLIT_MULTIPOINT:ON,ADD_SOUNDG_DEPTH=OFF,UPDATES=APPLY,LIST_AS_STRING=OFF,LNAM_REFS=ON").unwrap();
         unsafe {
             gdal_sys::GDALAllRegister();
             gdal_sys::CPLSetConfigOption(key.as_ptr(), value.as_ptr());
@@ -26,48 +40,129 @@ impl S57 {
         Dataset::open(path).map(|ds| S57 { dataset: ds }).ok()
     }
 
-    pub fn render_geojson(
+    /// DSID_INTU intended usage and DSPM_CSCL compilation scale from the DSID layer
+    pub fn dataset_parameters(&self) -> (Option<u8>, Option<u32>) {
+        self.dataset.layers()
+            .find(|layer| layer.name() == "DSID")
+            .and_then(|layer| layer.features().next().map(|feature| {
+                let props = gdal_feature_properties(&feature);
+                (
+                    props.get("DSID_INTU").and_then(|v| v.as_u64()).map(|v| v as u8),
+                    props.get("DSPM_CSCL").and_then(|v| v.as_u64()).map(|v| v as u32)
+                )
+            }))
+            .unwrap_or((None, None))
+    }
+
+    /// DSPM_DUNI and DSPM_HUNI units of depth and height measurement from the DSID layer
+    pub fn units(&self) -> Units {
+        self.dataset.layers()
+            .find(|layer| layer.name() == "DSID")
+            .and_then(|layer| layer.features().next().map(|feature| {
+                let props = gdal_feature_properties(&feature);
+                Units {
+                    duni: props.get("DSPM_DUNI").and_then(|v| v.as_i64()),
+                    huni: props.get("DSPM_HUNI").and_then(|v| v.as_i64()),
+                }
+            }))
+            .unwrap_or_default()
+    }
+
+    /// M_COVR polygons where data is available (CATCOV = 1)
+    pub fn coverage(&self) -> Vec<geojson::Value> {
+        let target_sr = SpatialRef::from_epsg(4326).unwrap();
+        self.dataset.layers()
+            .find(|layer| layer.name() == "M_COVR")
+            .and_then(|layer| feature_collection_from_layer(&layer, &target_sr, &Units::default(), None))
+            .map(|fc| fc.features.into_iter()
+                .filter(|f| f.properties.as_ref().and_then(|p| p.get("CATCOV")).and_then(|v| v.as_i64()) == Some(1))
+                .filter_map(|f| f.geometry.map(|g| g.value))
+                .collect())
+            .unwrap_or_default()
+    }
+
+    /// The rendered layers of the cell, the feature relationships `PARENT_LNAM` and `CHILDREN`
+    /// are written to the features' properties when `write_relations` is set. Given a date
+    /// CCYYMMDD `as_of` the features that do not exist on that date are left out.
+    ///
+    /// Every layer is read and symbolized before the excluded layers are left out, the symbols of
+    /// the conditional symbology procedures depend on the features of other layers eg the depth
+    /// areas around a wreck.
+    pub fn chart_layers(
         &self,
-        out_dir: &Path,
-        pretty: bool,
         ex_layers: Option<Vec<&str>>,
         in_layers: Option<Vec<&str>>,
-    ) -> Vec<String> {
-        println!("rendering geojson to: {:?}", out_dir);
-        utils::check_out_dir(out_dir);
-
-        let mut names: Vec<String> = vec![];
+        write_relations: bool,
+        as_of: Option<u32>,
+    ) -> Vec<ChartLayer> {
+        let units = self.units();
+        let mut relations = Relations::default();
+        let mut layers: Vec<ChartLayer> = vec![];
         let target_sr = SpatialRef::from_epsg(4326).unwrap();
         let layer_ex_set: Option<HashSet<_>> = ex_layers.map(|ea| ea.iter().cloned().collect());
         let layer_in_set: Option<HashSet<_>> = in_layers.map(|ea| ea.iter().cloned().collect());
 
         for layer in self.dataset.layers() {
-            let name = layer.name();
+            // features are indexed before they are left out so the relationships stay complete
+            let mut collection = feature_collection_from_layer(&layer, &target_sr, &units, Some(&mut relations));
+            if let (Some(collection), Some(date)) = (&mut collection, as_of) {
+                collection.features.retain(|f| f.properties.as_ref().map(|p| dates::in_effect(p, date)).unwrap_or(true));
+            }
+            if let Some(collection) = collection.filter(|c| !c.features.is_empty()) {
+                let fields = layer_fields(&layer);
+                layers.push(ChartLayer { name: layer.name(), fields, collection });
+            };
+        }
+        geojson_builder::process_layers(&mut layers, &relations, write_relations);
+        lookups::symbolize_layers(&mut layers, MarinerSettings::default());
+        resare::label_points(&mut layers, &resare::REGULATED);
+        resare::label_points(&mut layers, &tsslpt::ROUTEING);
+        bridge::clearance_labels(&mut layers);
+        topmar::associate_topmarks(&mut layers, &relations);
+        if let Some(sectors) = lights::sector_layer(&layers) {
+            layers.push(sectors);
+        }
+        layers.retain(|layer| {
+            // the light sectors are rendered with the lights
+            let name = if layer.name == lights::SECTORS_LAYER { "LIGHTS" } else { layer.name.as_str() };
             if let Some(exclude) = &layer_ex_set {
-                if exclude.contains(&name.as_str()) {
-                    println!("excluding layer: {}", &name);
-                    continue;
+                if exclude.contains(name) {
+                    println!("excluding layer: {}", &layer.name);
+                    return false;
                 }
             };
 
             if let Some(include) = &layer_in_set {
-                if !include.contains(&name.as_str()) {
-                    println!("skipping layer: {}", &name);
-                    continue;
+                if !include.contains(name) {
+                    println!("skipping layer: {}", &layer.name);
+                    return false;
                 }
             };
-            if let Some(fc) = feature_collection_from_layer(&layer, &target_sr) {
-                let layer_json = format!("{}.json", &name);
-                let mut json_out_path = PathBuf::from(out_dir);
-                json_out_path.push(layer_json.clone());
-                names.push(format!("{}", json_out_path.to_str().unwrap()));
-                let geo_json = if pretty {
-                    serde_json::to_string_pretty(&fc).unwrap()
-                } else {
-                    fc.to_string()
-                };
-                utils::write_json(out_dir, layer_json.as_str(), &geo_json);
+            true
+        });
+        layers
+    }
+
+    pub fn render_geojson(
+        out_dir: &Path,
+        pretty: bool,
+        layers: &Vec<ChartLayer>,
+    ) -> Vec<String> {
+        println!("rendering geojson to: {:?}", out_dir);
+        utils::check_out_dir(out_dir);
+
+        let mut names: Vec<String> = vec![];
+        for layer in layers {
+            let layer_json = format!("{}.json", &layer.name);
+            let mut json_out_path = PathBuf::from(out_dir);
+            json_out_path.push(layer_json.clone());
+            names.push(format!("{}", json_out_path.to_str().unwrap()));
+            let geo_json = if pretty {
+                serde_json::to_string_pretty(&layer.collection).unwrap()
+            } else {
+                layer.collection.to_string()
             };
+            utils::write_json(out_dir, layer_json.as_str(), &geo_json);
         }
         let meta = serde_json::json!({
             "layers": names
@@ -76,23 +171,110 @@ impl S57 {
         return names;
     }
 
-    pub fn generate_mbtiles(out_dir: &Path, geojson_files: &Vec<String>) {
-        let mb_tiles_out = format!("{}/chart.mbtiles", out_dir.to_str().unwrap());
-        let mb_tiles_out_p = Path::new(&mb_tiles_out);
-        if mb_tiles_out_p.exists() {
-            fs::remove_file(&mb_tiles_out_p).expect("could not remove existing mbtiles file");
+    pub fn generate_mbtiles(out_dir: &Path, name: &str, layers: &Vec<ChartLayer>, min_zoom: u8, max_zoom: u8) {
+        utils::check_out_dir(out_dir);
+        let mb_tiles_out = out_dir.join("chart.mbtiles");
+        println!("rendering tiles to: {:?}", mb_tiles_out);
+        let mb_tiles = MbTiles::create(&mb_tiles_out);
+        tiler::render_tiles(layers, min_zoom, max_zoom, |z, x, y, data| {
+            mb_tiles.insert_tile(z, x, y, &data);
+        });
+        mb_tiles.finish(&TileMetadata::new(name, layers, min_zoom, max_zoom));
+    }
+
+    pub fn generate_pmtiles(out_dir: &Path, name: &str, layers: &Vec<ChartLayer>, min_zoom: u8, max_zoom: u8) {
+        utils::check_out_dir(out_dir);
+        let pm_tiles_out = out_dir.join("chart.pmtiles");
+        println!("rendering tiles to: {:?}", pm_tiles_out);
+        let mut pm_tiles = PmTiles::create(&pm_tiles_out);
+        tiler::render_tiles(layers, min_zoom, max_zoom, |z, x, y, data| {
+            pm_tiles.insert_tile(z, x, y, &data);
+        });
+        pm_tiles.finish(&TileMetadata::new(name, layers, min_zoom, max_zoom));
+    }
+}
+
+/// Combines the layers of several cells, features of layers with the same name are concatenated
+pub fn merge_layers(cells: Vec<Vec<ChartLayer>>) -> Vec<ChartLayer> {
+    let mut merged: Vec<ChartLayer> = vec![];
+    for layer in cells.into_iter().flatten() {
+        match merged.iter_mut().find(|m| m.name == layer.name) {
+            Some(existing) => {
+                existing.fields.extend(layer.fields);
+                existing.collection.features.extend(layer.collection.features);
+            }
+            None => merged.push(layer),
+        }
+    }
+    merged
+}
+
+/// Attribute fields from the GDAL layer definition. S57 lists are rendered as json arrays which
+/// end up as strings in the vector tiles.
+fn layer_fields(layer: &gdal::vector::Layer) -> BTreeMap<String, String> {
+    layer.defn().fields().map(|field| {
+        let field_type = match field.field_type() {
+            OGRFieldType::OFTInteger | OGRFieldType::OFTInteger64 | OGRFieldType::OFTReal => "Number",
+            _ => "String",
         };
+        (field.name(), String::from(field_type))
+    }).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+    use crate::geojson_builder::JsonObject;
+    use crate::relations::PARENT_LNAM;
+
+    fn chart() -> S57 {
+        let chart = Path::new(file!())
+            .parent().unwrap()
+            .parent().unwrap()
+            .join("data")
+            .join("charts")
+            .join("US5WA22M")
+            .join("US5WA22M.000");
+        S57::open(&chart).unwrap()
+    }
+
+    #[test]
+    fn test_chart_layers_symbolized() {
+        let layers = chart().chart_layers(None, Some(vec!["DEPARE", "DEPCNT", "LIGHTS"]), false, None);
+        let features = |name: &str| layers.iter()
+            .find(|l| l.name == name).unwrap()
+            .collection.features.iter()
+            .filter_map(|f| f.properties.clone())
+            .collect::<Vec<_>>();
+
+        // the 3m safety contour is displayed on the 3.6m contour
+        for contour in features("DEPCNT") {
+            let safety = contour.get("VALDCO") == Some(&json!(3.6));
+            assert_eq!(safety, contour.contains_key("SCNT"));
+        }
+        // the depth area colours of the shallow 2m, safety 3m and deep 9m contours
+        for area in features("DEPARE").iter().filter(|a| a.contains_key("AC")) {
+            let drval1 = area["DRVAL1"].as_f64().unwrap();
+            let expected = if drval1 < 0.0 { "DEPIT" } else if drval1 < 2.0 { "DEPVS" } else if drval1 < 3.0 { "DEPMS" } else if drval1 < 9.0 { "DEPMD" } else { "DEPDW" };
+            assert_eq!(json!(expected), area["AC"]);
+        }
+        assert!(features("LIGHTS").iter().all(|l| l.contains_key("SY")));
+    }
 
-        let output = Command::new("tippecanoe")
-            .arg("-zg")
-            .arg("-o")
-            .arg(mb_tiles_out)
-            .arg("--coalesce-densest-as-needed")
-            .arg("--extend-zooms-if-still-dropping")
-            .args(geojson_files)
-            .output()
-            .expect("failed to execute process");
-        println!("{}", std::str::from_utf8(output.stdout.as_slice()).unwrap());
-        println!("{}", std::str::from_utf8(output.stderr.as_slice()).unwrap());
+    #[test]
+    fn test_chart_layers_relations() {
+        let layers = chart().chart_layers(None, None, true, Some(20210101));
+        let properties: Vec<&JsonObject> = layers.iter()
+            .flat_map(|l| l.collection.features.iter())
+            .filter_map(|f| f.properties.as_ref())
+            .collect();
+        let lnams: HashSet<&str> = properties.iter().filter_map(|p| p.get("LNAM")?.as_str()).collect();
+        let parents: Vec<&str> = properties.iter().filter_map(|p| p.get(PARENT_LNAM)?.as_str()).collect();
+        // the five aggregations C_AGGR of US5WA22M have no geometry and are not rendered
+        assert!(parents.iter().any(|parent| !lnams.contains(parent)));
+        // the lights of the buoys and beacons
+        let lights = layers.iter().find(|l| l.name == "LIGHTS").unwrap();
+        assert!(lights.collection.features.iter().any(|f| f.properties.as_ref().unwrap().contains_key(PARENT_LNAM)));
     }
 }
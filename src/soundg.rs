@@ -1,32 +1,57 @@
 use serde_json::{Value, json};
 use geojson::Geometry;
 use crate::geojson_builder::JsonObject;
+use crate::csp::{self, MarinerSettings};
 
 static FEET: &str = "FEET";
 static FATHOMS: &str = "FATHOMS";
 static FATHOMS_FT: &str = "FATHOMS_FT";
 static METERS: &str = "METERS";
+/// The tenths of a sounding in meters when they are shown
+static METERS_DEC: &str = "METERS_DEC";
+static DEPTH: &str = "DEPTH";
+/// A drying height, underlined
+static SDRY: &str = "SDRY";
+/// A sounding of low accuracy, circled
+static SLAC: &str = "SLAC";
+/// Whether the least depth of a hazard is drawn, see `csp::obstrn07`
+static SNDFRM: &str = "SNDFRM";
 
+/// Classes whose least depth VALSOU is labelled like a sounding
+const HAZARDS: [&str; 3] = ["OBSTRN", "UWTROC", "WRECKS"];
+
+/// The glyph components of a sounding (S-52 SNDFRM04). Drying heights are shown by their
+/// value underlined. Values are truncated, tenths of a meter are shown for depths shallower than
+/// 31m and drying heights less than 10m.
 struct Sounding {
+    depth_meters: f64,
     feet_display: i64,
     fathoms_display: i64,
     fathoms_feet_display: i64,
     meters_display: i64,
+    meters_decimal: Option<i64>,
 }
 
 impl Sounding {
 
     fn from(depth_meters: f64) -> Sounding {
-        let meters_display = depth_meters as i64;
-        let feet_display = (depth_meters * 3.28084_f64) as i64;
-        let fathoms = depth_meters * 0.546807_f64;
+        let value = depth_meters.abs();
+        // tenths with a tolerance so that eg 1.8 is not truncated to 1.7
+        let tenths = (value * 10.0 + 1e-6) as i64;
+        let meters_display = tenths / 10;
+        let decimals = if depth_meters < 0.0 { value < 10.0 } else { value < 31.0 };
+        let meters_decimal = Some(tenths % 10).filter(|d| decimals && *d != 0);
+        let feet_display = (value * 3.28084_f64) as i64;
+        let fathoms = value * 0.546807_f64;
         let fathoms_display = fathoms as i64;
         let fathoms_feet_display = ((fathoms - (fathoms_display as f64)) * 6_f64) as i64;
         Sounding {
+            depth_meters,
             feet_display,
             fathoms_display,
             fathoms_feet_display,
             meters_display,
+            meters_decimal,
         }
     }
 
@@ -35,9 +60,23 @@ impl Sounding {
         properties.insert(String::from(FATHOMS), json!(self.fathoms_display));
         properties.insert(String::from(FATHOMS_FT), json!(self.fathoms_feet_display));
         properties.insert(String::from(METERS), json!(self.meters_display));
+        if let Some(decimal) = self.meters_decimal {
+            properties.insert(String::from(METERS_DEC), json!(decimal));
+        }
+        properties.insert(String::from(DEPTH), json!(self.depth_meters));
+        properties.insert(String::from(SDRY), json!(self.depth_meters < 0.0));
+        properties.insert(String::from(SLAC), json!(low_accuracy(properties)));
     }
 }
 
+/// Low accuracy of the position QUAPOS or of the sounding QUASOU
+///     QUASOU 3 doubtful sounding, 4 unreliable sounding, 5 no bottom found at value shown,
+///     8 value reported (not surveyed), 9 value reported (not confirmed)
+fn low_accuracy(properties: &JsonObject) -> bool {
+    csp::low_accuracy(properties)
+        || csp::list_values(properties, "QUASOU").iter().any(|q| [3, 4, 5, 8, 9].contains(q))
+}
+
 /// Sounding SOUNDG
 /// Geometric primitives: Point
 ///
@@ -55,108 +94,143 @@ impl Sounding {
 ///     Drying heights (drying soundings) are indicated by a negative value.
 /// Distinction:
 ///     depth area; wreck; underwater/awash rock; obstruction;
-pub fn layers(colors: &Value, depth: &String) -> Vec<Value> {
-    match depth.as_str() {
-        "fathoms" => vec![
-            json!({
-              "id": "soundg_fathoms",
-              "type": "symbol",
-              "source": "src_senc",
-              "source-layer": "SOUNDG",
-              "filter": [ "any", [ "==", "$type", "Point" ], ],
-              "layout": {
-                "text-font": [ "Roboto Bold" ],
-                "text-anchor": "bottom-right",
-                "text-justify": "center",
-                "text-field": ["get", "FATHOMS"],
-                "text-allow-overlap": true,
-                "text-ignore-placement": true,
-                "text-size": 11,
-                "symbol-placement": "point"
-              },
-              "paint": {
-                "text-color": ["case", ["<=", ["get", "METERS"], 9.0], colors["SNDG2"], colors["SNDG1"] ],
-                "text-halo-color": colors["CHWHT"],
-                "text-halo-width": 1.5
-              }
-            }),
-            json!({
-              "id": "soundg_fathoms_feet",
-              "type": "symbol",
-              "source": "src_senc",
-              "source-layer": "SOUNDG",
-              "filter": [
-                "all",
-                [ "==", "$type", "Point" ],
-                [ "!=", "FATHOMS_FT", 0]
-              ],
-              "layout": {
-                "text-font": [
-                  "Roboto Bold"
-                ],
-                "text-anchor": "top-left",
-                "text-offset": [0.1, -0.7],
-                "text-justify": "center",
-                "text-field": ["get", "FATHOMS_FT"],
-                "text-allow-overlap": true,
-                "text-ignore-placement": true,
-                "text-size": 9,
-                "symbol-placement": "point"
-              },
-              "paint": {
-                "text-color": ["case", ["<=", ["get", "METERS"], 9.0], colors["SNDG2"], colors["SNDG1"] ]
-              }
-            })
-        ],
-        "feet" => vec![
-            json!({
-              "id": "soundg_feet",
-              "type": "symbol",
-              "source": "src_senc",
-              "source-layer": "SOUNDG",
-              "filter": [ "any", [ "==", "$type", "Point" ], ],
-              "layout": {
-                "text-font": [ "Roboto Bold" ],
-                "text-anchor": "center",
-                "text-justify": "center",
-                "text-field": ["get", "FEET"],
-                "text-allow-overlap": true,
-                "text-ignore-placement": true,
-                "text-size": 11,
-                "symbol-placement": "point"
-              },
-              "paint": {
-                "text-color": ["case", ["<=", ["get", "METERS"], 9.0], colors["SNDG2"], colors["SNDG1"] ],
-                "text-halo-color": colors["CHWHT"],
-                "text-halo-width": 1.5
-              }
-            })
-        ],
-        "meters" | _ => vec![
-            json!({
-              "id": "soundg_meters",
-              "type": "symbol",
-              "source": "src_senc",
-              "source-layer": "SOUNDG",
-              "filter": [ "any", [ "==", "$type", "Point" ], ],
-              "layout": {
-                "text-font": [ "Roboto Bold" ],
-                "text-anchor": "center",
-                "text-justify": "center",
-                "text-field": ["get", "METERS"],
-                "text-allow-overlap": true,
-                "text-ignore-placement": true,
-                "text-size": 11,
-                "symbol-placement": "point"
-              },
-              "paint": {
-                "text-color": ["case", ["<=", ["get", "METERS"], 9.0], colors["SNDG2"], colors["SNDG1"] ],
-                "text-halo-color": colors["CHWHT"],
-                "text-halo-width": 1.5
-              }
-            })
-        ]
+pub fn layers(colors: &Value, depth: &String, settings: &MarinerSettings) -> Vec<Value> {
+    let point = json!(["==", "$type", "Point"]);
+    let mut layers = depth_layers("soundg", "SOUNDG", json!(["any", point]), colors, depth, settings);
+    for class in HAZARDS.iter() {
+        let filter = json!(["all", point, ["==", SNDFRM, true]]);
+        layers.append(&mut depth_layers(&format!("{}_soundg", class), class, filter, colors, depth, settings));
+    }
+    layers
+}
+
+/// Depth labels in the chart's depth units of the point features of a source layer. The figure
+/// is followed by its subscript (the tenths of meters or the feet of fathoms), drying heights are
+/// underlined and soundings of low accuracy are circled.
+fn depth_layers(
+    id: &str,
+    source_layer: &str,
+    filter: Value,
+    colors: &Value,
+    depth: &str,
+    settings: &MarinerSettings,
+) -> Vec<Value> {
+    let text_color = json!(["case", ["<=", ["get", DEPTH], settings.safety_depth], colors["SNDG2"], colors["SNDG1"]]);
+    let (field, anchor) = match depth {
+        "fathoms" => (FATHOMS, json!("bottom-right")),
+        "feet" => (FEET, json!("center")),
+        _ => (METERS, json!(["case", ["has", METERS_DEC], "right", "center"])),
+    };
+    let mut layers = vec![
+        json!({
+          "id": format!("{}_{}", id, depth_name(depth)),
+          "type": "symbol",
+          "source": "src_senc",
+          "source-layer": source_layer,
+          "filter": filter.clone(),
+          "layout": {
+            "text-font": [ "Roboto Bold" ],
+            "text-anchor": anchor.clone(),
+            "text-justify": "center",
+            "text-field": ["get", field],
+            "text-allow-overlap": true,
+            "text-ignore-placement": true,
+            "text-size": 11,
+            "symbol-placement": "point"
+          },
+          "paint": {
+            "text-color": text_color.clone(),
+            "text-halo-color": colors["CHWHT"],
+            "text-halo-width": 1.5
+          }
+        })
+    ];
+    let subscript = match depth {
+        "fathoms" => Some(("fathoms_feet", FATHOMS_FT, json!(["!=", FATHOMS_FT, 0]), "top-left", json!([0.1, -0.7]))),
+        "feet" => None,
+        _ => Some(("meters_decimal", METERS_DEC, json!(["has", METERS_DEC]), "left", json!([0.05, 0.3]))),
+    };
+    if let Some((suffix, subscript_field, has_subscript, subscript_anchor, offset)) = subscript {
+        layers.push(json!({
+          "id": format!("{}_{}", id, suffix),
+          "type": "symbol",
+          "source": "src_senc",
+          "source-layer": source_layer,
+          "filter": ["all", filter.clone(), has_subscript],
+          "layout": {
+            "text-font": [
+              "Roboto Bold"
+            ],
+            "text-anchor": subscript_anchor,
+            "text-offset": offset,
+            "text-justify": "center",
+            "text-field": ["get", subscript_field],
+            "text-allow-overlap": true,
+            "text-ignore-placement": true,
+            "text-size": 9,
+            "symbol-placement": "point"
+          },
+          "paint": {
+            "text-color": text_color.clone()
+          }
+        }));
+    }
+    layers.push(json!({
+      "id": format!("{}_drying", id),
+      "type": "symbol",
+      "source": "src_senc",
+      "source-layer": source_layer,
+      "filter": ["all", filter.clone(), ["==", SDRY, true]],
+      "layout": {
+        "text-font": [ "Roboto Bold" ],
+        "text-anchor": anchor,
+        "text-field": ["get", field],
+        "text-size": 11,
+        "text-allow-overlap": true,
+        "text-ignore-placement": true,
+        "icon-image": "SOUNDGA1",
+        "icon-text-fit": "width",
+        "icon-text-fit-padding": [0, 1, 0, 1],
+        "icon-offset": [0, 7],
+        "icon-allow-overlap": true,
+        "icon-ignore-placement": true,
+        "symbol-placement": "point"
+      },
+      "paint": {
+        "text-opacity": 0
+      }
+    }));
+    layers.push(json!({
+      "id": format!("{}_low_accuracy", id),
+      "type": "symbol",
+      "source": "src_senc",
+      "source-layer": source_layer,
+      "filter": ["all", filter, ["==", SLAC, true]],
+      "layout": {
+        "icon-image": "SOUNDGC2",
+        "icon-size": 0.8,
+        "icon-allow-overlap": true,
+        "icon-ignore-placement": true,
+        "symbol-placement": "point"
+      }
+    }));
+    layers
+}
+
+fn depth_name(depth: &str) -> &str {
+    match depth {
+        "fathoms" | "feet" => depth,
+        _ => "meters",
+    }
+}
+
+/// Adds the display values of a hazard's least depth VALSOU so it is labelled like a sounding, the
+/// processor of obstructions OBSTRN, underwater rocks UWTROC and wrecks WRECKS
+pub fn process_valsou(geojson_geom: Geometry, properties: &mut JsonObject) -> Geometry {
+    if let Some(valsou) = properties.get("VALSOU").and_then(|v| v.as_f64()) {
+        Sounding::from(valsou).insert_into(properties);
     }
+    geojson_geom
 }
 
 pub fn process_sounding(geojson_geom: Geometry, properties: &mut JsonObject) -> Geometry {
@@ -172,6 +246,71 @@ pub fn process_sounding(geojson_geom: Geometry, properties: &mut JsonObject) ->
     }
 }
 
+/// Conditional symbology procedure SOUNDG03
+///
+/// Flags the presentation of the sounding, the sounding itself is drawn by the SOUNDG layers
+/// which colour it SNDG2 at or shallower than the mariner's safety depth, otherwise SNDG1
+///     SDRY - a drying height
+///     SLAC - low accuracy position or sounding
+pub fn soundg03(properties: &mut JsonObject) -> String {
+    let depth = properties.get(DEPTH).and_then(|v| v.as_f64()).unwrap_or(0.0);
+    properties.insert(String::from(SDRY), json!(depth < 0.0));
+    let low_accuracy = low_accuracy(properties);
+    properties.insert(String::from(SLAC), json!(low_accuracy));
+    String::new()
+}
+
+#[test]
+fn test_soundg03() {
+    let mut shallow = JsonObject::new();
+    Sounding::from(1.8).insert_into(&mut shallow);
+    soundg03(&mut shallow);
+    assert_eq!(json!(false), shallow["SDRY"]);
+
+    let mut deep = JsonObject::new();
+    Sounding::from(14.6).insert_into(&mut deep);
+    deep.insert(String::from("QUASOU"), json!(["4"]));
+    soundg03(&mut deep);
+    assert_eq!(json!(true), deep["SLAC"]);
+
+    let mut drying = JsonObject::new();
+    Sounding::from(-1.2).insert_into(&mut drying);
+    soundg03(&mut drying);
+    assert_eq!(json!(true), drying["SDRY"]);
+}
+
+#[test]
+fn test_process_valsou() {
+    let mut properties = json!({"VALSOU": 4.2}).as_object().unwrap().clone();
+    process_valsou(Geometry::new(geojson::Value::Point(vec![-122.4, 47.3])), &mut properties);
+    assert_eq!(json!(4), properties[METERS]);
+    assert_eq!(json!(13), properties[FEET]);
+    assert_eq!(json!(4.2), properties[DEPTH]);
+}
+
+#[test]
+fn test_layers() {
+    let colors = crate::colors::COLORS["DAY_BRIGHT"].clone();
+    let layers = layers(&colors, &String::from("fathoms"), &MarinerSettings::default());
+    let ids: Vec<&str> = layers.iter().map(|l| l["id"].as_str().unwrap()).collect();
+    assert_eq!(vec![
+        "soundg_fathoms", "soundg_fathoms_feet",
+        "OBSTRN_soundg_fathoms", "OBSTRN_soundg_fathoms_feet",
+        "UWTROC_soundg_fathoms", "UWTROC_soundg_fathoms_feet",
+        "WRECKS_soundg_fathoms", "WRECKS_soundg_fathoms_feet",
+    ], ids.iter().filter(|id| id.contains("fathoms")).cloned().collect::<Vec<&str>>());
+    assert_eq!(16, ids.len());
+    assert_eq!(json!(["all", ["any", ["==", "$type", "Point"]], ["==", "SDRY", true]]), layers[2]["filter"]);
+    assert_eq!(json!(["all", ["==", "$type", "Point"], ["==", "SNDFRM", true]]), layers[4]["filter"]);
+
+    let layers = self::layers(&colors, &String::from("meters"), &MarinerSettings::default());
+    let ids: Vec<&str> = layers.iter().map(|l| l["id"].as_str().unwrap()).collect();
+    assert_eq!(vec!["soundg_meters", "soundg_meters_decimal", "soundg_drying", "soundg_low_accuracy"], ids[..4].to_vec());
+    assert_eq!(json!("SOUNDGC2"), layers[3]["layout"]["icon-image"]);
+    let layers = self::layers(&colors, &String::from("feet"), &MarinerSettings::default());
+    assert_eq!(12, layers.len());
+}
+
 #[test]
 fn test_soundings() {
     let mut subject = Sounding::from(0.9);
@@ -185,4 +324,27 @@ fn test_soundings() {
     assert_eq!(976_i64, subject.feet_display); //976.70604 ft
     assert_eq!(162_i64, subject.fathoms_display); //162.78434 fathoms
     assert_eq!(4_i64, subject.fathoms_feet_display); //4.70604 fathoms
+    assert_eq!(None, subject.meters_decimal);
+}
+
+#[test]
+fn test_sounding_glyphs() {
+    let subject = Sounding::from(1.8);
+    assert_eq!((1, Some(8)), (subject.meters_display, subject.meters_decimal));
+    let subject = Sounding::from(5.67);
+    assert_eq!((5, Some(6)), (subject.meters_display, subject.meters_decimal));
+    let subject = Sounding::from(12.0);
+    assert_eq!((12, None), (subject.meters_display, subject.meters_decimal));
+    let subject = Sounding::from(35.4);
+    assert_eq!((35, None), (subject.meters_display, subject.meters_decimal));
+
+    let mut drying = json!({"QUASOU": ["3"]}).as_object().unwrap().clone();
+    Sounding::from(-1.2).insert_into(&mut drying);
+    assert_eq!(json!(1), drying[METERS]);
+    assert_eq!(json!(2), drying[METERS_DEC]);
+    assert_eq!(json!(3), drying[FEET]);
+    assert_eq!(json!(true), drying[SDRY]);
+    assert_eq!(json!(true), drying[SLAC]);
+    let subject = Sounding::from(-12.4);
+    assert_eq!((12, None), (subject.meters_display, subject.meters_decimal));
 }
\ No newline at end of file
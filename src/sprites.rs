@@ -0,0 +1,36 @@
+use std::collections::HashMap;
+use std::include_bytes;
+use serde_json::{Value, from_slice};
+
+/// The sprite sheet index of the S-52 raster symbols, every colour scheme has the same symbols
+const SPRITES_JSON: &[u8] = include_bytes!("../data/sprites/rastersymbols-day.json");
+
+lazy_static! {
+    /// symbol names and their width and height in pixels
+    static ref SYMBOLS: HashMap<String, (f64, f64)> = {
+        let json: Value = from_slice(SPRITES_JSON).unwrap();
+        json.as_object().map(|symbols| symbols.iter().map(|(name, image)| {
+            let dimension = |key: &str| image[key].as_f64().unwrap_or(0.0) / image["pixelRatio"].as_f64().unwrap_or(1.0);
+            (name.clone(), (dimension("width"), dimension("height")))
+        }).collect()).unwrap_or_default()
+    };
+}
+
+/// Whether the sprite sheet has an image for the symbol eg BCNSTK02
+pub fn has_symbol(name: &str) -> bool {
+    SYMBOLS.contains_key(name)
+}
+
+/// The width and height of the symbol's image in pixels
+pub fn size(name: &str) -> Option<(f64, f64)> {
+    SYMBOLS.get(name).cloned()
+}
+
+#[test]
+fn test_has_symbol() {
+    assert!(has_symbol("BCNSTK02"));
+    assert!(has_symbol("BOYPIL61"));
+    assert!(!has_symbol("BCNCON81"));
+    assert_eq!(Some((18.0, 18.0)), size("BOYPIL61"));
+    assert_eq!(Some((8.0, 6.0)), size("TOPMAR02"));
+}
@@ -1,6 +1,7 @@
 use std::path::Path;
-use crate::{utils, soundg, seaare, depare, depcnt, boyspp, lights};
+use crate::{utils, soundg, lights, presentation, topmar, bridge, cblsub, depcnt};
 use crate::colors;
+use crate::csp::MarinerSettings;
 use serde_json::json;
 use serde_json::Value;
 
@@ -49,17 +50,18 @@ pub fn create_config(out_dir: &Path, domain_list: Vec<String>) {
 pub fn create_style(
     out_dir: &Path,
     base_url: &String,
+    settings: &MarinerSettings,
 ) {
     utils::check_out_dir(out_dir);
     for depth in depths() {
         for color in colors::COLOR_KEYS.iter() {
-            let style_json = create_substyle(&base_url, &depth, &color);
+            let style_json = create_substyle(&base_url, &depth, &color, settings);
             utils::write_json(out_dir, format!("{}_{}_style.json", color, depth).as_str(), &style_json.to_string());
         }
     }
 }
 
-fn create_substyle(base_url: &String, depth: &String, color: &String) -> Value {
+fn create_substyle(base_url: &String, depth: &String, color: &String, settings: &MarinerSettings) -> Value {
     let json_style = json!({
       "version": 8,
       "name": format!("{}-{}", color, depth),
@@ -71,11 +73,11 @@ fn create_substyle(base_url: &String, depth: &String, color: &String) -> Value {
       },
       "sprite": format!("rastersymbols-{}", color),
       "glyphs": format!("{}/fonts/{{fontstack}}/{{range}}.pbf", base_url),
-      "layers": style_layers(depth, color)} );
+      "layers": style_layers(depth, color, settings)} );
     return json_style;
 }
 
-fn style_layers(depth: &String, color: &String) -> Value {
+fn style_layers(depth: &String, color: &String, settings: &MarinerSettings) -> Value {
     let colors = match color.as_str() {
         "dusk" => colors::COLORS["DUSK"].clone(),
         "dark" => colors::COLORS["NIGHT"].clone(),
@@ -92,123 +94,13 @@ fn style_layers(depth: &String, color: &String) -> Value {
     }
     ]);
     if let Value::Array(ref mut items) = value {
-        items.append(&mut seaare::layers(&colors));
-        items.append(&mut depare::layers(&colors));
-        items.append(&mut depcnt::layers(&colors));
-        items.append(&mut todo_layers(&colors));
-        items.append(&mut soundg::layers(&colors, &depth));
-        items.append(&mut boyspp::layers());
-        items.append(&mut lights::layers());
+        items.append(&mut presentation::layers(&colors, settings));
+        items.append(&mut depcnt::layers(&colors, &depth, settings));
+        items.append(&mut cblsub::layers());
+        items.append(&mut topmar::layers());
+        items.append(&mut lights::layers(&colors, &depth));
+        items.append(&mut bridge::layers(&colors, &depth));
+        items.append(&mut soundg::layers(&colors, &depth, settings));
     };
     return value;
 }
-
-fn todo_layers(colors: &Value) -> Vec<Value> {
-    vec![
-        json!({
-            "id": "SLCONS_line",
-            "type": "line",
-            "source": "src_senc",
-            "source-layer": "SLCONS",
-            "filter": [ "all" ],
-            "paint": {
-                "line-color": colors["CSTLN"],
-                "line-width": 1
-            }
-        }),
-        json!({
-            "id": "PONTON_fill",
-            "type": "fill",
-            "source": "src_senc",
-            "source-layer": "PONTON",
-            "filter": [ "all", [ "==", "$type", "Polygon" ] ],
-            "paint": {
-                "fill-color": "#B7911F"
-            }
-        }),
-        json!({
-            "id": "PONTON_line",
-            "type": "line",
-            "source": "src_senc",
-            "source-layer": "PONTON",
-            "filter": [ "any", [ "==", "$type", "Polygon" ], [ "==", "$type", "LineString" ] ],
-            "paint": {
-                "line-color": colors["CSTLN"],
-                "line-width": 1
-            }
-        }),
-        json!({
-            "id": "HULKES_fill",
-            "type": "fill",
-            "source": "src_senc",
-            "source-layer": "HULKES",
-            "filter": [ "any", [ "==", "$type", "Polygon" ] ],
-            "paint": {
-                "fill-color": "#B7911F"
-            }
-        }),
-        json!({
-            "id": "HULKES_line",
-            "type": "line",
-            "source": "src_senc",
-            "source-layer": "HULKES",
-            "filter": [
-            "any",
-            [
-                "==",
-                "$type",
-                "Polygon"
-            ],
-            [
-                "==",
-                "$type",
-                "LineString"
-            ]
-            ],
-            "paint": {
-                "line-color": colors["CSTLN"],
-                "line-width": 1.5
-            }
-        }),
-        json!({
-            "id": "LNDARE_fill",
-            "type": "fill",
-            "source": "src_senc",
-            "source-layer": "LNDARE",
-            "filter": [
-            "any",
-            [
-                "==",
-                "$type",
-                "Polygon"
-            ]
-            ],
-            "paint": {
-                "fill-color": colors["LANDA"]
-            }
-        }),
-        json!({
-            "id": "LNDARE_line",
-            "type": "line",
-            "source": "src_senc",
-            "source-layer": "LNDARE",
-            "filter": [
-            "any",
-            [
-                "==",
-                "$type",
-                "Polygon"
-            ],
-            [
-                "==",
-                "$type",
-                "LineString"
-            ]
-            ],
-            "paint": {
-                "line-color": colors["CSTLN"],
-                "line-width": 2
-            }
-        })
-    ]
-}
@@ -0,0 +1,32 @@
+use std::collections::BTreeMap;
+use serde_json::Value;
+use geojson::{Geometry, Value as GeoValue};
+use crate::geojson_builder::Processor;
+use crate::s57::ChartLayer;
+
+/// The symbol SY a processor gives a point feature with the properties
+pub fn point_symbol(processor: Processor, value: Value) -> Option<Value> {
+    let mut properties = value.as_object().unwrap().clone();
+    let point = Geometry::new(geojson::Value::Point(vec![-122.4, 47.3]));
+    processor(point, &mut properties);
+    properties.get("SY").cloned()
+}
+
+/// A layer of features with the geometries and properties
+pub fn chart_layer(name: &str, features: Vec<(GeoValue, Value)>) -> ChartLayer {
+    ChartLayer {
+        name: String::from(name),
+        fields: BTreeMap::new(),
+        collection: geojson::FeatureCollection {
+            bbox: None,
+            features: features.into_iter().map(|(geometry, properties)| geojson::Feature {
+                bbox: None,
+                geometry: Some(Geometry::new(geometry)),
+                id: None,
+                properties: Some(properties.as_object().unwrap().clone()),
+                foreign_members: None,
+            }).collect(),
+            foreign_members: None,
+        },
+    }
+}
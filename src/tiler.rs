@@ -0,0 +1,634 @@
+use std::collections::BTreeMap;
+use std::f64::consts::PI;
+use geojson::Value;
+use serde_json::json;
+use crate::geojson_builder::JsonObject;
+use crate::mvt;
+use crate::mvt::GeomType;
+use crate::s57::ChartLayer;
+
+/// Tile buffer in tile units, geometry this far outside of a tile is kept so that lines and
+/// symbols do not get cut at tile boundaries.
+const BUFFER: f64 = 64.0;
+const MAX_LAT: f64 = 85.0511287798066;
+/// Ground resolution in meters per pixel at the equator at zoom 0 for the 512 pixel tiles
+/// Mapbox GL displays vector tiles at.
+const EQUATOR_RESOLUTION: f64 = 78_271.517;
+/// The 0.28mm standardized rendering pixel size (OGC WMTS) used to relate display scale to zoom
+const PIXEL_SIZE: f64 = 0.000_28;
+/// The deepest zoom level tiles are rendered at
+pub const MAX_ZOOM: u8 = 24;
+/// Geometry is simplified to this distance in tile units at every zoom before it is encoded
+const SIMPLIFY_TOLERANCE: f64 = 1.0;
+
+type Point = (f64, f64);
+
+/// Geometry projected into normalized web mercator space where (0, 0) is the north west corner
+/// and (1, 1) is the south east corner of the world.
+enum Geom {
+    Points(Vec<Point>),
+    Lines(Vec<Vec<Point>>),
+    Polygons(Vec<Vec<Vec<Point>>>),
+}
+
+struct Prepared<'a> {
+    geom: Geom,
+    bbox: [f64; 4],
+    min_zoom: u8,
+    max_zoom: u8,
+    properties: &'a JsonObject,
+}
+
+/// Tileset level information shared by the tile archive writers
+pub struct TileMetadata {
+    pub name: String,
+    pub min_zoom: u8,
+    pub max_zoom: u8,
+    /// west, south, east, north
+    pub bounds: [f64; 4],
+    pub vector_layers: serde_json::Value,
+}
+
+impl TileMetadata {
+    pub fn new(name: &str, layers: &[ChartLayer], min_zoom: u8, max_zoom: u8) -> TileMetadata {
+        TileMetadata {
+            name: String::from(name),
+            min_zoom,
+            max_zoom,
+            bounds: bounds(layers),
+            vector_layers: vector_layers(layers, min_zoom, max_zoom),
+        }
+    }
+
+    /// longitude, latitude, zoom
+    pub fn center(&self) -> (f64, f64, u8) {
+        (
+            (self.bounds[0] + self.bounds[2]) / 2.0,
+            (self.bounds[1] + self.bounds[3]) / 2.0,
+            ((self.min_zoom as u16 + self.max_zoom as u16) / 2) as u8
+        )
+    }
+}
+
+/// The longitude latitude bounds of all the features in the layers
+fn bounds(layers: &[ChartLayer]) -> [f64; 4] {
+    let mut bounds = [180.0, 90.0, -180.0, -90.0];
+    let mut extend = |p: &Vec<f64>| {
+        bounds[0] = p[0].min(bounds[0]);
+        bounds[1] = p[1].min(bounds[1]);
+        bounds[2] = p[0].max(bounds[2]);
+        bounds[3] = p[1].max(bounds[3]);
+    };
+    layers.iter()
+        .flat_map(|layer| layer.collection.features.iter())
+        .filter_map(|f| f.geometry.as_ref())
+        .for_each(|g| match &g.value {
+            Value::Point(p) => extend(p),
+            Value::MultiPoint(mp) | Value::LineString(mp) => mp.iter().for_each(&mut extend),
+            Value::MultiLineString(ml) | Value::Polygon(ml) => ml.iter().flatten().for_each(&mut extend),
+            Value::MultiPolygon(mp) => mp.iter().flatten().flatten().for_each(&mut extend),
+            Value::GeometryCollection(_) => {}
+        });
+    if bounds[0] > bounds[2] {
+        [-180.0, -85.0511, 180.0, 85.0511]
+    } else {
+        bounds
+    }
+}
+
+/// TileJSON vector_layers, fields come from the GDAL layer definition with any additional
+/// properties added while processing features (eg SY) typed from their values.
+fn vector_layers(layers: &[ChartLayer], min_zoom: u8, max_zoom: u8) -> serde_json::Value {
+    let vector_layers: Vec<serde_json::Value> = layers.iter().map(|layer| {
+        let mut fields = layer.fields.clone();
+        layer.collection.features.iter()
+            .filter_map(|f| f.properties.as_ref())
+            .flat_map(|p| p.iter())
+            .for_each(|(key, value)| {
+                if !fields.contains_key(key) {
+                    let field_type = match value {
+                        serde_json::Value::Number(_) => "Number",
+                        serde_json::Value::Bool(_) => "Boolean",
+                        _ => "String",
+                    };
+                    fields.insert(key.clone(), String::from(field_type));
+                }
+            });
+        json!({
+            "id": layer.name,
+            "description": "",
+            "minzoom": min_zoom,
+            "maxzoom": max_zoom,
+            "fields": fields
+        })
+    }).collect();
+    serde_json::Value::Array(vector_layers)
+}
+
+pub fn lng_lat_to_mercator(lng: f64, lat: f64) -> Point {
+    let lat = lat.max(-MAX_LAT).min(MAX_LAT).to_radians();
+    let x = (lng + 180.0) / 360.0;
+    let y = (1.0 - (lat.tan() + 1.0 / lat.cos()).ln() / PI) / 2.0;
+    (x, y)
+}
+
+/// The latitude of a normalized web mercator y coordinate
+fn mercator_to_lat(y: f64) -> f64 {
+    (PI * (1.0 - 2.0 * y)).sinh().atan().to_degrees()
+}
+
+/// Ground distance in meters of a distance in millimeters on screen at the zoom and latitude
+pub fn screen_distance(millimeters: f64, zoom: u8, lat: f64) -> f64 {
+    let resolution = EQUATOR_RESOLUTION * lat.max(-MAX_LAT).min(MAX_LAT).to_radians().cos() / 2_f64.powi(zoom as i32);
+    millimeters / 1000.0 / PIXEL_SIZE * resolution
+}
+
+/// SCAMIN, Scale minimum
+///
+/// Definition:
+///     The minimum scale at which the object may be used eg for ECDIS presentation. The value is
+///     the denominator of the display scale, 1:22000 is encoded as 22000.
+/// References
+///     S-57: Appendix A, Chapter 2, 2.2.1
+///
+/// The first zoom level where the display scale at the latitude is at least as large as SCAMIN
+pub fn scamin_zoom(scamin: f64, lat: f64) -> u8 {
+    if scamin <= 0.0 {
+        return 0;
+    }
+    let resolution = EQUATOR_RESOLUTION * lat.max(-MAX_LAT).min(MAX_LAT).to_radians().cos();
+    let zoom = (resolution / (scamin * PIXEL_SIZE)).log2().ceil();
+    zoom.max(0.0).min(u8::MAX as f64) as u8
+}
+
+/// Renders the chart layers into Mapbox vector tiles for every zoom level in the range. Each
+/// non empty tile is handed to the `sink` with its zoom, x and y (xyz scheme) coordinates.
+/// Features with a SCAMIN attribute are left out of the tiles zoomed out further than their
+/// minimum display scale, features beyond `max_zoom` are kept in the `max_zoom` tiles for overzoom.
+/// Lines and areas are simplified to the resolution of the zoom, areas smaller than it are left out.
+pub fn render_tiles<F>(
+    layers: &[ChartLayer],
+    min_zoom: u8,
+    max_zoom: u8,
+    mut sink: F,
+) where F: FnMut(u8, u32, u32, Vec<u8>) {
+    let prepared: Vec<(&str, Vec<Prepared>)> = layers.iter().map(|layer| {
+        let features = layer.collection.features.iter().filter_map(|f| {
+            match (&f.geometry, &f.properties) {
+                (Some(geometry), Some(properties)) => prepare(&geometry.value, properties).map(|mut p| {
+                    let (min, max) = zoom_range(f);
+                    let scamin = properties.get("SCAMIN").and_then(|s| s.as_f64()).map(|scamin| {
+                        let lat = mercator_to_lat((p.bbox[1] + p.bbox[3]) / 2.0);
+                        scamin_zoom(scamin, lat).min(max_zoom)
+                    });
+                    p.min_zoom = min.max(scamin.unwrap_or(0));
+                    p.max_zoom = max;
+                    p
+                }),
+                _ => None
+            }
+        }).collect();
+        (layer.name.as_str(), features)
+    }).collect();
+
+    for z in min_zoom..=max_zoom {
+        let mut tiles: BTreeMap<(u32, u32), mvt::Tile> = BTreeMap::new();
+        let n = 2_f64.powi(z as i32);
+        let max_tile = (1_u32 << z) - 1;
+        let buffer = BUFFER / mvt::EXTENT as f64;
+        let tolerance = SIMPLIFY_TOLERANCE / (n * mvt::EXTENT as f64);
+        for (name, features) in &prepared {
+            for feature in features.iter().filter(|f| f.min_zoom <= z && z <= f.max_zoom) {
+                let geom = match simplify(&feature.geom, tolerance) {
+                    Some(geom) => geom,
+                    None => continue,
+                };
+                let x0 = ((feature.bbox[0] * n - buffer).floor().max(0.0) as u32).min(max_tile);
+                let y0 = ((feature.bbox[1] * n - buffer).floor().max(0.0) as u32).min(max_tile);
+                let x1 = ((feature.bbox[2] * n + buffer).floor().max(0.0) as u32).min(max_tile);
+                let y1 = ((feature.bbox[3] * n + buffer).floor().max(0.0) as u32).min(max_tile);
+                for x in x0..=x1 {
+                    for y in y0..=y1 {
+                        if let Some((geom_type, geometry)) = clip_to_tile(&geom, n, x, y) {
+                            tiles.entry((x, y))
+                                .or_insert_with(mvt::Tile::new)
+                                .layer(name)
+                                .add_feature(geom_type, geometry, feature.properties);
+                        }
+                    }
+                }
+            }
+        }
+        println!("zoom {} - {} tiles", z, tiles.len());
+        for ((x, y), tile) in tiles {
+            if !tile.is_empty() {
+                sink(z, x, y, tile.encode());
+            }
+        }
+    }
+}
+
+fn prepare<'a>(value: &Value, properties: &'a JsonObject) -> Option<Prepared<'a>> {
+    let project = |p: &Vec<f64>| lng_lat_to_mercator(p[0], p[1]);
+    let project_line = |l: &Vec<Vec<f64>>| l.iter().map(project).collect::<Vec<Point>>();
+    let project_poly = |p: &Vec<Vec<Vec<f64>>>| p.iter().map(project_line).collect::<Vec<Vec<Point>>>();
+    let geom = match value {
+        Value::Point(p) => Geom::Points(vec![project(p)]),
+        Value::MultiPoint(mp) => Geom::Points(mp.iter().map(project).collect()),
+        Value::LineString(l) => Geom::Lines(vec![project_line(l)]),
+        Value::MultiLineString(ml) => Geom::Lines(ml.iter().map(project_line).collect()),
+        Value::Polygon(p) => Geom::Polygons(vec![project_poly(p)]),
+        Value::MultiPolygon(mp) => Geom::Polygons(mp.iter().map(project_poly).collect()),
+        Value::GeometryCollection(_) => return None,
+    };
+    let mut bbox = [f64::MAX, f64::MAX, f64::MIN, f64::MIN];
+    let mut extend = |p: &Point| {
+        bbox[0] = bbox[0].min(p.0);
+        bbox[1] = bbox[1].min(p.1);
+        bbox[2] = bbox[2].max(p.0);
+        bbox[3] = bbox[3].max(p.1);
+    };
+    match &geom {
+        Geom::Points(points) => points.iter().for_each(&mut extend),
+        Geom::Lines(lines) => lines.iter().flatten().for_each(&mut extend),
+        Geom::Polygons(polygons) => polygons.iter().flatten().flatten().for_each(&mut extend),
+    };
+    if bbox[0] > bbox[2] {
+        None
+    } else {
+        Some(Prepared { geom, bbox, min_zoom: 0, max_zoom: u8::MAX, properties })
+    }
+}
+
+/// The geometry simplified with the Douglas-Peucker algorithm to the tolerance, None when a line
+/// or area is smaller than the tolerance
+fn simplify(geom: &Geom, tolerance: f64) -> Option<Geom> {
+    let simplified = match geom {
+        Geom::Points(points) => Geom::Points(points.clone()),
+        Geom::Lines(lines) => Geom::Lines(lines.iter()
+            .map(|line| douglas_peucker(line, tolerance))
+            .filter(|line| line.len() >= 2)
+            .collect()),
+        Geom::Polygons(polygons) => Geom::Polygons(polygons.iter()
+            .map(|polygon| polygon.iter()
+                .map(|ring| douglas_peucker(ring, tolerance))
+                .filter(|ring| ring.len() >= 4)
+                .collect::<Vec<Vec<Point>>>())
+            // the exterior ring is first, without it the holes are dropped as well
+            .filter(|polygon| polygon.first().map(|ring| extent(ring) >= tolerance).unwrap_or(false))
+            .collect()),
+    };
+    let is_empty = match &simplified {
+        Geom::Points(points) => points.is_empty(),
+        Geom::Lines(lines) => lines.is_empty(),
+        Geom::Polygons(polygons) => polygons.is_empty(),
+    };
+    if is_empty {
+        None
+    } else {
+        Some(simplified)
+    }
+}
+
+/// The width or height of the ring's bounding box, whichever is larger
+fn extent(ring: &[Point]) -> f64 {
+    let (min, max) = ring.iter().fold(((f64::MAX, f64::MAX), (f64::MIN, f64::MIN)), |(min, max), p| {
+        ((min.0.min(p.0), min.1.min(p.1)), (max.0.max(p.0), max.1.max(p.1)))
+    });
+    (max.0 - min.0).max(max.1 - min.1)
+}
+
+fn douglas_peucker(line: &[Point], tolerance: f64) -> Vec<Point> {
+    if line.len() <= 2 {
+        return line.to_vec();
+    }
+    let mut keep = vec![false; line.len()];
+    keep[0] = true;
+    keep[line.len() - 1] = true;
+    let mut stack = vec![(0, line.len() - 1)];
+    while let Some((first, last)) = stack.pop() {
+        let (a, b) = (line[first], line[last]);
+        let farthest = (first + 1..last)
+            .map(|i| (i, segment_distance(line[i], a, b)))
+            .fold((first, 0.0), |far, (i, d)| if d > far.1 { (i, d) } else { far });
+        if farthest.1 > tolerance {
+            keep[farthest.0] = true;
+            stack.push((first, farthest.0));
+            stack.push((farthest.0, last));
+        }
+    }
+    line.iter().zip(keep).filter(|(_, keep)| *keep).map(|(p, _)| *p).collect()
+}
+
+/// Distance of the point to the segment a b
+fn segment_distance(p: Point, a: Point, b: Point) -> f64 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length = dx * dx + dy * dy;
+    let t = if length == 0.0 { 0.0 } else { (((p.0 - a.0) * dx + (p.1 - a.1) * dy) / length).max(0.0).min(1.0) };
+    ((p.0 - a.0 - t * dx).powi(2) + (p.1 - a.1 - t * dy).powi(2)).sqrt()
+}
+
+/// The feature's zoom range from the tippecanoe foreign member eg
+/// `"tippecanoe": { "minzoom": 14, "maxzoom": 16 }`
+pub fn zoom_range(feature: &geojson::Feature) -> (u8, u8) {
+    let tippecanoe = feature.foreign_members.as_ref().and_then(|m| m.get("tippecanoe"));
+    let zoom = |key: &str| tippecanoe.and_then(|t| t.get(key)).and_then(|z| z.as_u64()).map(|z| z as u8);
+    (zoom("minzoom").unwrap_or(0), zoom("maxzoom").unwrap_or(u8::MAX))
+}
+
+/// Transforms the geometry into tile space for tile x, y at scale n (2^z), clips it to the
+/// buffered tile bounds and encodes it. Returns None when nothing remains inside the tile.
+fn clip_to_tile(geom: &Geom, n: f64, x: u32, y: u32) -> Option<(GeomType, Vec<u32>)> {
+    let extent = mvt::EXTENT as f64;
+    let to_tile = |p: &Point| ((p.0 * n - x as f64) * extent, (p.1 * n - y as f64) * extent);
+    let min = -BUFFER;
+    let max = extent + BUFFER;
+    let (geom_type, geometry) = match geom {
+        Geom::Points(points) => {
+            let tile_points: Vec<(i32, i32)> = points.iter()
+                .map(to_tile)
+                .filter(|p| p.0 >= min && p.0 <= max && p.1 >= min && p.1 <= max)
+                .map(|p| quantize(&p))
+                .collect();
+            (GeomType::Point, mvt::encode_points(&tile_points))
+        }
+        Geom::Lines(lines) => {
+            let tile_lines: Vec<Vec<(i32, i32)>> = lines.iter()
+                .flat_map(|line| clip_line(&line.iter().map(to_tile).collect::<Vec<Point>>(), min, max))
+                .map(|line| dedup(line.iter().map(quantize).collect()))
+                .filter(|line| line.len() >= 2)
+                .collect();
+            (GeomType::LineString, mvt::encode_lines(&tile_lines))
+        }
+        Geom::Polygons(polygons) => {
+            let mut tile_rings: Vec<Vec<(i32, i32)>> = vec![];
+            for polygon in polygons {
+                let mut rings = polygon.iter()
+                    .map(|ring| clip_ring(&ring.iter().map(to_tile).collect::<Vec<Point>>(), min, max))
+                    .map(|ring| close_dedup(dedup(ring.iter().map(quantize).collect())));
+                // the exterior ring is first, when it is clipped away the holes are dropped as well
+                match rings.next() {
+                    Some(exterior) if exterior.len() >= 3 && ring_area(&exterior) != 0 => {
+                        tile_rings.push(wind(exterior, true));
+                        rings.filter(|r| r.len() >= 3 && ring_area(r) != 0)
+                            .for_each(|r| tile_rings.push(wind(r, false)));
+                    }
+                    _ => {}
+                }
+            }
+            (GeomType::Polygon, mvt::encode_rings(&tile_rings))
+        }
+    };
+    if geometry.is_empty() {
+        None
+    } else {
+        Some((geom_type, geometry))
+    }
+}
+
+fn quantize(p: &Point) -> (i32, i32) {
+    (p.0.round() as i32, p.1.round() as i32)
+}
+
+fn dedup(mut points: Vec<(i32, i32)>) -> Vec<(i32, i32)> {
+    points.dedup();
+    points
+}
+
+/// Removes the repeated closing point of a ring, the MVT ClosePath command implies it
+fn close_dedup(mut ring: Vec<(i32, i32)>) -> Vec<(i32, i32)> {
+    while ring.len() > 1 && ring.first() == ring.last() {
+        ring.pop();
+    }
+    ring
+}
+
+/// Twice the signed area of a ring in tile space (y down), positive when clockwise on screen
+fn ring_area(ring: &[(i32, i32)]) -> i64 {
+    let mut area = 0_i64;
+    for i in 0..ring.len() {
+        let (x1, y1) = ring[i];
+        let (x2, y2) = ring[(i + 1) % ring.len()];
+        area += x1 as i64 * y2 as i64 - x2 as i64 * y1 as i64;
+    }
+    area
+}
+
+fn wind(mut ring: Vec<(i32, i32)>, exterior: bool) -> Vec<(i32, i32)> {
+    if (ring_area(&ring) > 0) != exterior {
+        ring.reverse();
+    }
+    ring
+}
+
+/// Liang-Barsky clipping of a poly line against the square [min, max], the line is split into
+/// multiple parts where it leaves and re-enters the square.
+fn clip_line(line: &[Point], min: f64, max: f64) -> Vec<Vec<Point>> {
+    let mut parts: Vec<Vec<Point>> = vec![];
+    let mut current: Vec<Point> = vec![];
+    for seg in line.windows(2) {
+        match clip_segment(seg[0], seg[1], min, max) {
+            Some((a, b)) => {
+                if current.last() != Some(&a) {
+                    if current.len() >= 2 {
+                        parts.push(current);
+                    }
+                    current = vec![a];
+                }
+                current.push(b);
+            }
+            None => {
+                if current.len() >= 2 {
+                    parts.push(current);
+                }
+                current = vec![];
+            }
+        }
+    }
+    if current.len() >= 2 {
+        parts.push(current);
+    }
+    parts
+}
+
+fn clip_segment(a: Point, b: Point, min: f64, max: f64) -> Option<(Point, Point)> {
+    let dx = b.0 - a.0;
+    let dy = b.1 - a.1;
+    let mut t0 = 0.0_f64;
+    let mut t1 = 1.0_f64;
+    for (p, q) in [(-dx, a.0 - min), (dx, max - a.0), (-dy, a.1 - min), (dy, max - a.1)].iter() {
+        if *p == 0.0 {
+            if *q < 0.0 {
+                return None;
+            }
+        } else {
+            let r = q / p;
+            if *p < 0.0 {
+                t0 = t0.max(r);
+            } else {
+                t1 = t1.min(r);
+            }
+        }
+    }
+    if t0 > t1 {
+        None
+    } else {
+        Some(((a.0 + t0 * dx, a.1 + t0 * dy), (a.0 + t1 * dx, a.1 + t1 * dy)))
+    }
+}
+
+/// Sutherland-Hodgman clipping of a polygon ring against the square [min, max]
+fn clip_ring(ring: &[Point], min: f64, max: f64) -> Vec<Point> {
+    let mut output = ring.to_vec();
+    for edge in 0..4 {
+        let inside = |p: &Point| match edge {
+            0 => p.0 >= min,
+            1 => p.0 <= max,
+            2 => p.1 >= min,
+            _ => p.1 <= max,
+        };
+        let intersect = |a: &Point, b: &Point| {
+            let t = match edge {
+                0 => (min - a.0) / (b.0 - a.0),
+                1 => (max - a.0) / (b.0 - a.0),
+                2 => (min - a.1) / (b.1 - a.1),
+                _ => (max - a.1) / (b.1 - a.1),
+            };
+            (a.0 + t * (b.0 - a.0), a.1 + t * (b.1 - a.1))
+        };
+        let input = output;
+        output = vec![];
+        if input.is_empty() {
+            break;
+        }
+        let mut prev = input[input.len() - 1];
+        for p in input {
+            if inside(&p) {
+                if !inside(&prev) {
+                    output.push(intersect(&prev, &p));
+                }
+                output.push(p);
+            } else if inside(&prev) {
+                output.push(intersect(&prev, &p));
+            }
+            prev = p;
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+    use crate::test_util::chart_layer;
+
+    fn buoy_layer() -> ChartLayer {
+        chart_layer("BOYSPP", vec![(Value::Point(vec![-122.41757, 47.27888]), json!({"SY": "BOYCAN60"}))])
+    }
+
+    #[test]
+    fn test_mercator() {
+        let (x, y) = lng_lat_to_mercator(0.0, 0.0);
+        assert!((x - 0.5).abs() < 1e-9);
+        assert!((y - 0.5).abs() < 1e-9);
+        let (x, y) = lng_lat_to_mercator(-180.0, MAX_LAT);
+        assert!(x.abs() < 1e-9);
+        assert!(y.abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_render_point() {
+        let layers = vec![buoy_layer()];
+        let mut rendered = vec![];
+        render_tiles(&layers, 0, 14, |z, x, y, data| {
+            assert!(!data.is_empty());
+            rendered.push((z, x, y));
+        });
+        assert!((0..=14).all(|z| rendered.iter().any(|t| t.0 == z)));
+        assert!(rendered.contains(&(0, 0, 0)));
+        assert!(rendered.contains(&(14, 2620, 5744)));
+    }
+
+    #[test]
+    fn test_render_zoom_range() {
+        let mut layer = buoy_layer();
+        layer.collection.features[0].foreign_members = Some(
+            json!({"tippecanoe": {"minzoom": 10, "maxzoom": 12}}).as_object().unwrap().clone()
+        );
+        let mut zooms = vec![];
+        render_tiles(&[layer], 0, 14, |z, _, _, _| zooms.push(z));
+        zooms.dedup();
+        assert_eq!(vec![10, 11, 12], zooms);
+    }
+
+    #[test]
+    fn test_screen_distance() {
+        // 25mm is ~89 pixels, 426.5 meters at zoom 14 at the equator
+        assert!((screen_distance(25.0, 14, 0.0) - 426.5).abs() < 0.1);
+        assert!((screen_distance(25.0, 14, 60.0) - 213.3).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_scamin_zoom() {
+        assert_eq!(14, scamin_zoom(22_000.0, 47.6));
+        assert_eq!(12, scamin_zoom(90_000.0, 47.6));
+        assert_eq!(14, scamin_zoom(12_000.0, 47.6));
+        assert_eq!(0, scamin_zoom(0.0, 47.6));
+        assert!((mercator_to_lat(lng_lat_to_mercator(0.0, 47.6).1) - 47.6).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_render_scamin() {
+        let mut layer = buoy_layer();
+        layer.collection.features[0].properties.as_mut().unwrap().insert(String::from("SCAMIN"), json!(90_000));
+        let mut zooms = vec![];
+        render_tiles(&[layer], 0, 16, |z, _, _, _| zooms.push(z));
+        zooms.dedup();
+        assert_eq!((12..=16).collect::<Vec<u8>>(), zooms);
+    }
+
+    #[test]
+    fn test_metadata() {
+        let layers = vec![buoy_layer()];
+        let metadata = TileMetadata::new("US5WA22M", &layers, 0, 16);
+        assert_eq!([-122.41757, 47.27888, -122.41757, 47.27888], metadata.bounds);
+        assert_eq!((-122.41757, 47.27888, 8), metadata.center());
+        assert_eq!(json!([{
+            "id": "BOYSPP",
+            "description": "",
+            "minzoom": 0,
+            "maxzoom": 16,
+            "fields": { "SY": "String" }
+        }]), metadata.vector_layers);
+    }
+
+    #[test]
+    fn test_clip_ring() {
+        let square = vec![(-10.0, -10.0), (10.0, -10.0), (10.0, 10.0), (-10.0, 10.0)];
+        let clipped = clip_ring(&square, 0.0, 100.0);
+        let quantized: Vec<(i32, i32)> = clipped.iter().map(quantize).collect();
+        assert_eq!(4, quantized.len());
+        assert!(quantized.iter().all(|p| p.0 >= 0 && p.1 >= 0));
+        assert_eq!(200, ring_area(&quantized).abs());
+    }
+
+    #[test]
+    fn test_clip_line() {
+        let line = vec![(-10.0, 5.0), (50.0, 5.0), (150.0, 5.0), (150.0, 50.0), (50.0, 50.0)];
+        let parts = clip_line(&line, 0.0, 100.0);
+        assert_eq!(2, parts.len());
+        assert_eq!(vec![(0.0, 5.0), (50.0, 5.0), (100.0, 5.0)], parts[0]);
+        assert_eq!(vec![(100.0, 50.0), (50.0, 50.0)], parts[1]);
+    }
+
+    #[test]
+    fn test_simplify() {
+        let line = vec![(0.0, 0.0), (5.0, 0.4), (10.0, 0.0), (10.0, 10.0)];
+        assert_eq!(vec![(0.0, 0.0), (10.0, 0.0), (10.0, 10.0)], douglas_peucker(&line, 1.0));
+        assert_eq!(line, douglas_peucker(&line, 0.1));
+        let small = Geom::Polygons(vec![vec![vec![(0.0, 0.0), (0.0, 0.5), (0.5, 0.5), (0.5, 0.0), (0.0, 0.0)]]]);
+        assert!(simplify(&small, 1.0).is_none());
+        assert!(simplify(&small, 0.1).is_some());
+    }
+}
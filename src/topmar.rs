@@ -0,0 +1,183 @@
+use geojson::Value as GeoValue;
+use serde_json::{Value, json};
+use crate::geojson_builder::JsonObject;
+use crate::csp::{self, FLOATING, same_position};
+use crate::s57::ChartLayer;
+use crate::sprites;
+use crate::relations::Relations;
+
+/// Aids to navigation that carry topmarks
+const BEACONS: [&str; 5] = ["BCNCAR", "BCNISD", "BCNLAT", "BCNSAW", "BCNSPP"];
+
+/// The topmark symbol of the parent aid to navigation eg TOPMAR02
+pub const TOPMAR: &str = "TOPMAR";
+/// The height in pixels the topmark is raised above the parent's position, half the height of
+/// the parent's symbol so the topmark sits on top of the icon
+pub const TOPMAR_DY: &str = "TOPMAR_DY";
+
+/// Raise used when the parent has no symbol
+const DEFAULT_DY: f64 = 8.0;
+
+/// TOPMAR, Topmark
+/// Geometric primitives: Point
+///
+/// Set Attribute_A: 	(!)COLOUR; (!?)COLPAT; HEIGHT; MARSYS; STATUS; (!)TOPSHP; VERACC; VERLEN;
+/// Set Attribute_B: 	INFORM; NINFOM; NTXTDS; PICREP; SCAMAX; SCAMIN; TXTDSC;
+/// Set Attribute_C: 	RECDAT; RECIND; SORDAT; SORIND;
+///
+/// Definition:
+///     A characteristic shape secured at the top of a buoy or beacon to aid in its identification. (IHO Dictionary, S-32, 5th Edition, 5548)
+/// References
+///     INT 1:	IQ 9-11, 20-24, 70-71, 130;
+///     S-4:	463.5;
+/// Remarks:
+///     A topmark is a slave of the buoy or beacon it is secured to (FFPT), both share a position.
+/// Distinction:
+///     daymark;
+///
+/// Attaches the topmark symbol `TOPMAR` and its raise `TOPMAR_DY` to the buoys and beacons of
+/// the cell. A topmark belongs to the aid it is a slave of, or else to the aid at its position.
+/// The symbol of an attached topmark is removed from the TOPMAR layer so it is only drawn with
+/// its parent.
+pub fn associate_topmarks(layers: &mut [ChartLayer], relations: &Relations) {
+    let topmarks: Vec<(Option<String>, Vec<f64>, i64)> = layers.iter()
+        .filter(|layer| layer.name == "TOPMAR")
+        .flat_map(|layer| layer.collection.features.iter())
+        .filter_map(|feature| match (&feature.geometry, &feature.properties) {
+            (Some(geojson::Geometry { value: GeoValue::Point(p), .. }), Some(properties)) => {
+                let lnam = properties.get("LNAM").and_then(|v| v.as_str()).map(String::from);
+                let topshp = csp::list_values(properties, "TOPSHP").first().cloned().unwrap_or(0);
+                Some((lnam, p.clone(), topshp))
+            }
+            _ => None,
+        })
+        .collect();
+    if topmarks.is_empty() {
+        return;
+    }
+    let mut attached: Vec<Vec<f64>> = vec![];
+    for layer in layers.iter_mut() {
+        let floating = FLOATING.contains(&layer.name.as_str());
+        if !floating && !BEACONS.contains(&layer.name.as_str()) {
+            continue;
+        }
+        for feature in layer.collection.features.iter_mut() {
+            if let (Some(geojson::Geometry { value: GeoValue::Point(p), .. }), Some(properties)) =
+                (&feature.geometry, &mut feature.properties) {
+                let slaves = properties.get("LNAM").and_then(|v| v.as_str())
+                    .map(|lnam| relations.slaves_of_class(lnam, "TOPMAR"))
+                    .unwrap_or_default();
+                let topmark = topmarks.iter()
+                    .find(|(lnam, _, _)| lnam.as_ref().map(|l| slaves.contains(&l.as_str())).unwrap_or(false))
+                    .or_else(|| topmarks.iter().find(|(_, position, _)| same_position(position, p)));
+                if let Some((_, position, topshp)) = topmark {
+                    attach(properties, *topshp, floating);
+                    attached.push(position.clone());
+                }
+            }
+        }
+    }
+    for layer in layers.iter_mut().filter(|layer| layer.name == "TOPMAR") {
+        for feature in layer.collection.features.iter_mut() {
+            if let (Some(geojson::Geometry { value: GeoValue::Point(p), .. }), Some(properties)) =
+                (&feature.geometry, &mut feature.properties) {
+                if attached.iter().any(|position| same_position(position, p)) {
+                    properties.remove("SY");
+                }
+            }
+        }
+    }
+}
+
+fn attach(properties: &mut JsonObject, topshp: i64, floating: bool) {
+    let dy = properties.get("SY")
+        .and_then(|v| v.as_str())
+        .and_then(sprites::size)
+        .map(|(_, height)| (height / 2.0).round())
+        .unwrap_or(DEFAULT_DY);
+    properties.insert(String::from(TOPMAR), json!(csp::topmark_symbol(topshp, floating)));
+    properties.insert(String::from(TOPMAR_DY), json!(dy));
+}
+
+/// Topmark layers of the buoys and beacons drawn above the parent's symbol. Buoy symbols lean
+/// to the right so their topmarks are moved right a quarter of the raise.
+pub fn layers() -> Vec<Value> {
+    FLOATING.iter().chain(BEACONS.iter()).map(|class| {
+        let lean = if FLOATING.contains(class) { 0.25 } else { 0.0 };
+        json!({
+            "id": format!("{}_topmark", class),
+            "type": "symbol",
+            "source": "src_senc",
+            "source-layer": class,
+            "filter": ["has", TOPMAR],
+            "layout": {
+                "icon-image": ["get", TOPMAR],
+                "icon-anchor": "bottom",
+                "icon-offset": ["interpolate", ["linear"], ["get", TOPMAR_DY],
+                    0, ["literal", [0, 0]],
+                    32, ["literal", [32.0 * lean, -32]]
+                ],
+                "icon-allow-overlap": true,
+                "icon-ignore-placement": true,
+                "symbol-placement": "point"
+            }
+        })
+    }).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::chart_layer;
+
+    fn properties(layer: &ChartLayer, index: usize) -> &JsonObject {
+        layer.collection.features[index].properties.as_ref().unwrap()
+    }
+
+    #[test]
+    fn test_associate_topmarks() {
+        let mut layers = vec![
+            chart_layer("BOYCAR", vec![
+                (GeoValue::Point(vec![-122.4, 47.3]), json!({
+                    "SY": "BOYPIL68", "LNAM": "0226099A0B7E1F4C", "LNAM_REFS": ["0226099A0B7E1F4D"], "FFPT_RIND": [2]
+                })),
+                (GeoValue::Point(vec![-122.5, 47.3]), json!({"SY": "BOYPIL69"})),
+            ]),
+            chart_layer("BCNSPP", vec![
+                (GeoValue::Point(vec![-122.6, 47.3]), json!({"SY": "BCNSTK02"})),
+            ]),
+            chart_layer("TOPMAR", vec![
+                // the topmark of the north cardinal is referenced but not at the buoy's position
+                (GeoValue::Point(vec![-122.4001, 47.3]), json!({"SY": "TOPMAR02", "LNAM": "0226099A0B7E1F4D", "TOPSHP": 13})),
+                (GeoValue::Point(vec![-122.6, 47.3]), json!({"SY": "TOPMAR22", "LNAM": "0226099A0B7E1F4E", "TOPSHP": 1})),
+                (GeoValue::Point(vec![-122.7, 47.3]), json!({"SY": "TOPMAR22", "TOPSHP": 1})),
+            ]),
+        ];
+        let mut relations = Relations::default();
+        for layer in &layers {
+            for feature in &layer.collection.features {
+                relations.add(&layer.name, feature.properties.as_ref().unwrap());
+            }
+        }
+        associate_topmarks(&mut layers, &relations);
+        let north = properties(&layers[0], 0);
+        assert_eq!(json!("TOPMAR05"), north[TOPMAR]);
+        assert_eq!(json!(9.0), north[TOPMAR_DY]);
+        assert!(properties(&layers[0], 1).get(TOPMAR).is_none());
+        let stake = properties(&layers[1], 0);
+        assert_eq!(json!("TOPMAR22"), stake[TOPMAR]);
+        assert_eq!(json!(8.0), stake[TOPMAR_DY]);
+        assert!(properties(&layers[2], 1).get("SY").is_none());
+        // a topmark without a parent keeps its own symbol
+        assert_eq!(json!("TOPMAR22"), properties(&layers[2], 2)["SY"]);
+    }
+
+    #[test]
+    fn test_layers() {
+        let layers = layers();
+        let boycar = layers.iter().find(|l| l["id"] == "BOYCAR_topmark").unwrap();
+        assert_eq!(json!([8.0, -32]), boycar["layout"]["icon-offset"][6][1]);
+        let bcnlat = layers.iter().find(|l| l["id"] == "BCNLAT_topmark").unwrap();
+        assert_eq!(json!([0.0, -32]), bcnlat["layout"]["icon-offset"][6][1]);
+    }
+}
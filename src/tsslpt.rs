@@ -0,0 +1,65 @@
+use serde_json::{Value, json};
+use crate::geojson_builder::JsonObject;
+use geojson::Geometry;
+
+/// Routeing measures whose direction arrows and symbols are centered in the area
+pub const ROUTEING: [&str; 7] = ["DWRTPT", "FAIRWY", "RCTLPT", "RECTRC", "TSSLPT", "TSSRON", "TWRTPT"];
+
+/// TSSLPT, Traffic separation scheme lane part
+/// Geometric primitives: Area
+///
+/// Set Attribute_A: 	CATTSS; DATEND; DATSTA; (!)ORIENT; RESTRN; STATUS;
+/// Set Attribute_B: 	INFORM; NINFOM; NTXTDS; SCAMAX; SCAMIN; TXTDSC;
+/// Set Attribute_C: 	RECDAT; RECIND; SORDAT; SORIND;
+///
+/// Definition:
+///     An area of a traffic lane in which the direction of flow of traffic is uniform.
+/// References
+///     INT 1:	IM 10, 11;
+///     S-4:	433.1-2; 433.4;
+/// Remarks:
+///     The lane's arrow is rotated to the direction of traffic flow ORIENT by the style, the
+///     separation zones (TSEZNE) and boundaries (TSSBND) are separate objects.
+/// Distinction:
+///     traffic separation line; traffic separation scheme boundary; traffic separation zone;
+///     traffic separation scheme crossing; traffic separation scheme roundabout;
+///
+/// Writes the orientation ORIENT of a routeing measure as a number of degrees clockwise from
+/// true north in [0, 360), the rotation `SYRT` of its direction arrow. An orientation that is
+/// not a number is removed so the arrow's default symbol is used.
+pub fn process_orient(geojson_geom: Geometry, properties: &mut JsonObject) -> Geometry {
+    let orient = match properties.get("ORIENT") {
+        Some(Value::Number(n)) => n.as_f64(),
+        Some(Value::String(s)) => s.trim().parse::<f64>().ok(),
+        _ => None,
+    };
+    match orient.filter(|o| o.is_finite()) {
+        Some(orient) => {
+            properties.insert(String::from("ORIENT"), json!(orient.rem_euclid(360.0)));
+        }
+        None => {
+            properties.remove("ORIENT");
+        }
+    }
+    geojson_geom
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn orient(value: Value) -> Option<Value> {
+        let mut properties = value.as_object().unwrap().clone();
+        let point = Geometry::new(geojson::Value::Point(vec![-122.4, 47.3]));
+        process_orient(point, &mut properties);
+        properties.get("ORIENT").cloned()
+    }
+
+    #[test]
+    fn test_process_orient() {
+        assert_eq!(Some(json!(134.5)), orient(json!({"ORIENT": 134.5})));
+        assert_eq!(Some(json!(314.5)), orient(json!({"ORIENT": "-45.5"})));
+        assert_eq!(Some(json!(0.0)), orient(json!({"ORIENT": 360})));
+        assert_eq!(None, orient(json!({"ORIENT": "unknown"})));
+    }
+}
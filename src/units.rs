@@ -0,0 +1,124 @@
+use serde_json::{Value, json};
+use crate::geojson_builder::JsonObject;
+
+/// Depth attributes measured in the cell's units of depth DUNI
+const DEPTHS: [&str; 5] = ["BURDEP", "DRVAL1", "DRVAL2", "VALDCO", "VALSOU"];
+/// Height attributes measured in the cell's units of height HUNI
+const HEIGHTS: [&str; 7] = ["ELEVAT", "HEIGHT", "VERCCL", "VERCLR", "VERCOP", "VERCSA", "VERLEN"];
+
+const FEET_PER_METER: f64 = 3.28084;
+const FATHOMS_PER_METER: f64 = 0.546807;
+
+/// Units of depth DUNI and height HUNI measurement of a cell (DSPM)
+///
+/// DUNI
+///     1 metres, 2 fathoms and feet, 3 feet, 4 fathoms and fractions
+/// HUNI
+///     1 metres, 2 feet, 3 kilometres, 4 hectometres, 5 statute miles, 6 nautical miles
+///
+/// Cells that do not give their units are in metres.
+#[derive(Clone, Copy, Default)]
+pub struct Units {
+    pub duni: Option<i64>,
+    pub huni: Option<i64>,
+}
+
+impl Units {
+    pub fn depth_meters(&self, value: f64) -> f64 {
+        match self.duni {
+            Some(2) | Some(4) => value * 1.8288,
+            Some(3) => value * 0.3048,
+            _ => value,
+        }
+    }
+
+    pub fn height_meters(&self, value: f64) -> f64 {
+        match self.huni {
+            Some(2) => value * 0.3048,
+            Some(3) => value * 1000.0,
+            Some(4) => value * 100.0,
+            Some(5) => value * 1609.344,
+            Some(6) => value * 1852.0,
+            _ => value,
+        }
+    }
+
+    /// Converts the depth and height attributes of the feature to meters. Styles convert the
+    /// meters to their depth units with `depth_expression`.
+    pub fn normalize(&self, properties: &mut JsonObject) {
+        for (attributes, depth) in [(&DEPTHS[..], true), (&HEIGHTS[..], false)].iter() {
+            for attribute in attributes.iter() {
+                let value = match properties.get(*attribute).and_then(number) {
+                    Some(value) => value,
+                    None => continue,
+                };
+                let meters = if *depth { self.depth_meters(value) } else { self.height_meters(value) };
+                let value = if (meters - value).abs() > f64::EPSILON { exact(meters) } else { value };
+                properties.insert(attribute.to_string(), json!(value));
+            }
+        }
+    }
+
+    /// Converts the depth of a sounding, its third coordinate, to meters
+    pub fn normalize_sounding(&self, geometry: &mut geojson::Geometry) {
+        let depth_meters = |position: &mut Vec<f64>| {
+            if let Some(depth) = position.get_mut(2) {
+                *depth = self.depth_meters(*depth);
+            }
+        };
+        match &mut geometry.value {
+            geojson::Value::Point(position) => depth_meters(position),
+            geojson::Value::MultiPoint(positions) => positions.iter_mut().for_each(depth_meters),
+            _ => {}
+        }
+    }
+}
+
+/// A style expression of a depth or height attribute in meters converted to the depth units of a
+/// style eg `["*", ["get", "VALDCO"], 3.28084]` in feet
+pub fn depth_expression(attribute: &str, depth: &str) -> Value {
+    match depth {
+        "feet" => json!(["*", ["get", attribute], FEET_PER_METER]),
+        "fathoms" => json!(["*", ["get", attribute], FATHOMS_PER_METER]),
+        _ => json!(["get", attribute]),
+    }
+}
+
+fn number(value: &Value) -> Option<f64> {
+    value.as_f64().or_else(|| value.as_str().and_then(|s| s.trim().parse().ok()))
+}
+
+/// Removes the floating point error of a conversion, 30 ft are 9.144 m rather than
+/// 9.144000000000002 m
+fn exact(value: f64) -> f64 {
+    (value * 1e6).round() / 1e6
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        let mut contour = json!({"VALDCO": 30.0, "OBJNAM": "Foo"}).as_object().unwrap().clone();
+        Units { duni: Some(3), huni: None }.normalize(&mut contour);
+        assert_eq!(json!(9.144), contour["VALDCO"]);
+        assert_eq!(json!("Foo"), contour["OBJNAM"]);
+        assert_eq!(2, contour.len());
+
+        let mut bridge = json!({"VERCLR": "40", "DRVAL1": 5.55}).as_object().unwrap().clone();
+        Units { duni: Some(1), huni: Some(2) }.normalize(&mut bridge);
+        assert_eq!(json!(12.192), bridge["VERCLR"]);
+        assert_eq!(json!(5.55), bridge["DRVAL1"]);
+        assert_eq!(2, bridge.len());
+    }
+
+    #[test]
+    fn test_normalize_sounding() {
+        let mut sounding = geojson::Geometry::new(geojson::Value::Point(vec![-122.4, 47.3, 8.0]));
+        Units { duni: Some(4), huni: None }.normalize_sounding(&mut sounding);
+        assert_eq!(geojson::Value::Point(vec![-122.4, 47.3, 8.0 * 1.8288]), sounding.value);
+        assert_eq!(json!(["*", ["get", "VALDCO"], FATHOMS_PER_METER]), depth_expression("VALDCO", "fathoms"));
+        assert_eq!(json!(["get", "VALDCO"]), depth_expression("VALDCO", "meters"));
+    }
+}